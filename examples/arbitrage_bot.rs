@@ -1,7 +1,8 @@
-use mev_engine::amm::jediswap::pool::JediswapPool;
 use mev_engine::amm::pool::{AutomatedMarketMaker, AMM};
-use mev_engine::amm::tenkswap::pool::TenkSwapPool;
-use starknet::core::types::Felt;
+use mev_engine::amm::types::Amount;
+use mev_engine::amm::uniswap_v2::descriptor::{JEDISWAP, TENKSWAP};
+use mev_engine::amm::uniswap_v2::pool::UniswapV2Pool;
+use starknet::core::types::{BlockId, Felt};
 use starknet::providers::jsonrpc::HttpTransport;
 use starknet::providers::{JsonRpcClient, Provider, Url};
 use std::sync::Arc;
@@ -21,8 +22,10 @@ async fn find_arbitrage<P>(pool1: &mut AMM, pool2: &mut AMM, provider: Arc<P>) -
 where
     P: Provider + Send + Sync,
 {
-    pool1.sync(provider.clone()).await.unwrap();
-    pool2.sync(provider.clone()).await.unwrap();
+    // Read both pools at the same block so their prices are comparable
+    let block_id = BlockId::Number(provider.block_number().await.unwrap());
+    pool1.sync(block_id, provider.clone()).await.unwrap();
+    pool2.sync(block_id, provider).await.unwrap();
 
     let mut pool1_tokens = pool1.tokens();
     let mut pool2_tokens = pool2.tokens();
@@ -36,15 +39,13 @@ where
 
     let tokens = pool1_tokens;
 
-    let amount_in = Felt::from(100u32);
+    let amount_in = Amount::from(100u32);
     let amount_out = pool1
-        .simulate_swap(tokens[0], amount_in, provider.clone())
-        .await
+        .simulate_swap(tokens[0], tokens[1], amount_in)
         .unwrap();
 
     let final_amount = pool2
-        .simulate_swap(tokens[1], amount_out, provider)
-        .await
+        .simulate_swap(tokens[1], tokens[0], amount_out)
         .unwrap();
 
     if final_amount <= amount_in {
@@ -67,22 +68,22 @@ async fn main() {
     let rpc_url = "https://starknet-mainnet.public.blastapi.io/rpc/v0_7";
     let provider = create_rpc_provider(rpc_url).unwrap();
 
-    let mut tenkswap_pool = AMM::TenkSwapPool(
-        TenkSwapPool::new_from_address(
+    let mut tenkswap_pool = AMM::UniswapV2Pool(
+        UniswapV2Pool::new_from_address(
             Felt::from_hex("0x17e9e62c04b50800d7c59454754fe31a2193c9c3c6c92c093f2ab0faadf8c87")
                 .unwrap(),
-            300u32,
+            &TENKSWAP,
             provider.clone(),
         )
         .await
         .unwrap(),
     );
 
-    let mut jediswap_pool = AMM::JediswapPool(
-        JediswapPool::new_from_address(
+    let mut jediswap_pool = AMM::UniswapV2Pool(
+        UniswapV2Pool::new_from_address(
             Felt::from_hex("0x7e2a13b40fc1119ec55e0bcf9428eedaa581ab3c924561ad4e955f95da63138")
                 .unwrap(),
-            300u32,
+            &JEDISWAP,
             provider.clone(),
         )
         .await
@@ -106,7 +107,7 @@ async fn main() {
 
     // pool.simulate_swap(
     //     Felt::from_hex("0xda114221cb83fa859dbdb4c44beeaa0bb37c7537ad5ae66fe5e0efd20e6eb3").unwrap(),
-    //     Felt::from(100u32),
+    //     Amount::from(100u32),
     //     provider.clone(),
     // )
     // .await
@@ -1,5 +1,7 @@
-use mev_engine::amm::jediswap::pool::JediswapPool;
 use mev_engine::amm::pool::AutomatedMarketMaker;
+use mev_engine::amm::types::Amount;
+use mev_engine::amm::uniswap_v2::descriptor::JEDISWAP;
+use mev_engine::amm::uniswap_v2::pool::UniswapV2Pool;
 use starknet::core::types::Felt;
 use starknet::providers::jsonrpc::HttpTransport;
 use starknet::providers::{JsonRpcClient, Url};
@@ -22,21 +24,24 @@ async fn main() {
     let rpc_url = "https://starknet-mainnet.public.blastapi.io/rpc/v0_7";
     let provider = create_rpc_provider(rpc_url).unwrap();
 
-    let pool = JediswapPool::new_from_address(
+    let pool = UniswapV2Pool::new_from_address(
         Felt::from_hex("0x04d0390b777b424e43839cd1e744799f3de6c176c7e32c1812a41dbd9c19db6a")
             .unwrap(),
-        0u32,
+        &JEDISWAP,
         provider.clone(),
     )
     .await
     .unwrap();
 
-    pool.simulate_swap(
+    let token_in =
         Felt::from_hex("0x53c91253bc9682c04929ca02ed00b3e423f6710d2ee7e0d5ebb06f3ecf368a8")
-            .unwrap(),
-        Felt::from(100u32),
-        provider,
-    )
-    .await
-    .unwrap();
+            .unwrap();
+    let token_out = pool
+        .tokens()
+        .into_iter()
+        .find(|token| *token != token_in)
+        .unwrap();
+
+    pool.simulate_swap(token_in, token_out, Amount::from(100u32))
+        .unwrap();
 }
@@ -1,5 +1,7 @@
 use mev_engine::amm::pool::AutomatedMarketMaker;
-use mev_engine::amm::tenkswap::pool::TenkSwapPool;
+use mev_engine::amm::types::Amount;
+use mev_engine::amm::uniswap_v2::descriptor::TENKSWAP;
+use mev_engine::amm::uniswap_v2::pool::UniswapV2Pool;
 use starknet::core::types::Felt;
 use starknet::providers::jsonrpc::HttpTransport;
 use starknet::providers::{JsonRpcClient, Url};
@@ -19,20 +21,23 @@ async fn main() {
     let rpc_url = "https://starknet-mainnet.public.blastapi.io/rpc/v0_7";
     let provider = create_rpc_provider(rpc_url).unwrap();
 
-    let pool = TenkSwapPool::new_from_address(
+    let pool = UniswapV2Pool::new_from_address(
         Felt::from_hex("0x17e9e62c04b50800d7c59454754fe31a2193c9c3c6c92c093f2ab0faadf8c87")
             .unwrap(),
-        300u32,
+        &TENKSWAP,
         provider.clone(),
     )
     .await
     .unwrap();
 
-    pool.simulate_swap(
-        Felt::from_hex("0xda114221cb83fa859dbdb4c44beeaa0bb37c7537ad5ae66fe5e0efd20e6eb3").unwrap(),
-        Felt::from(100u32),
-        provider.clone(),
-    )
-    .await
-    .unwrap();
+    let token_in =
+        Felt::from_hex("0xda114221cb83fa859dbdb4c44beeaa0bb37c7537ad5ae66fe5e0efd20e6eb3").unwrap();
+    let token_out = pool
+        .tokens()
+        .into_iter()
+        .find(|token| *token != token_in)
+        .unwrap();
+
+    pool.simulate_swap(token_in, token_out, Amount::from(100u32))
+        .unwrap();
 }
@@ -0,0 +1,331 @@
+use num_bigint::BigUint;
+use num_traits::{FromPrimitive, One, ToPrimitive, Zero};
+use starknet::core::types::Felt;
+
+use super::{
+    pool::{AutomatedMarketMaker, AMM},
+    types::{Amount, Fee},
+};
+use crate::errors::AMMError;
+
+/// A hypothetical swap of a depth curve.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DepthPoint {
+    pub amount_in: Amount,
+    pub amount_out: Amount,
+    /// `amount_out / amount_in`, in raw token units.
+    pub execution_price: f64,
+    /// Price of `token_in` in `token_out` left in the pool after the swap, as returned by
+    /// [`AutomatedMarketMaker::calculate_price`].
+    pub price_after: f64,
+    /// `1 - price_after / price_before`.
+    pub price_impact: f64,
+}
+
+/// Price of `token_in` in `token_out` left in the pool after swapping `amount_in`. The swap
+/// is simulated on a copy, the pool passed in is left untouched.
+pub fn price_after_swap(
+    amm: &AMM,
+    token_in: Felt,
+    token_out: Felt,
+    amount_in: Amount,
+) -> Result<f64, AMMError> {
+    let mut pool = amm.clone();
+    pool.simulate_swap_mut(token_in, token_out, amount_in)?;
+    pool.calculate_price(token_in, token_out)
+}
+
+/// Relative move of the price of `token_in` in `token_out` caused by swapping `amount_in`,
+/// `0.01` meaning the price dropped by 1%. Fails with [`AMMError::ZeroSpotPrice`] when the
+/// pool holds none of `token_out`.
+pub fn price_impact(
+    amm: &AMM,
+    token_in: Felt,
+    token_out: Felt,
+    amount_in: Amount,
+) -> Result<f64, AMMError> {
+    let price_before = spot_price(amm, token_in, token_out)?;
+    let price_after = price_after_swap(amm, token_in, token_out, amount_in)?;
+    Ok(1.0 - price_after / price_before)
+}
+
+/// Simulates swapping each of `amounts` of `token_in` for `token_out`, every one starting
+/// from the current pool state, and returns the execution price and price impact of each.
+/// Fails like [`price_impact`] when the spot price is zero.
+pub fn depth_curve(
+    amm: &AMM,
+    token_in: Felt,
+    token_out: Felt,
+    amounts: &[Amount],
+) -> Result<Vec<DepthPoint>, AMMError> {
+    let price_before = spot_price(amm, token_in, token_out)?;
+
+    let mut curve = Vec::with_capacity(amounts.len());
+    for &amount_in in amounts {
+        let mut pool = amm.clone();
+        let amount_out = pool.simulate_swap_mut(token_in, token_out, amount_in)?;
+        let price_after = pool.calculate_price(token_in, token_out)?;
+
+        let execution_price = if amount_in.is_zero() {
+            0.0
+        } else {
+            to_f64(amount_out) / to_f64(amount_in)
+        };
+
+        curve.push(DepthPoint {
+            amount_in,
+            amount_out,
+            execution_price,
+            price_after,
+            price_impact: 1.0 - price_after / price_before,
+        });
+    }
+
+    Ok(curve)
+}
+
+/// Price of `token_in` in `token_out` before any swap, which price impacts are relative to.
+fn spot_price(amm: &AMM, token_in: Felt, token_out: Felt) -> Result<f64, AMMError> {
+    let price = amm.calculate_price(token_in, token_out)?;
+    if price == 0.0 {
+        return Err(AMMError::ZeroSpotPrice(amm.address()));
+    }
+
+    Ok(price)
+}
+
+/// Smallest amount of `token_in` that moves the price of `token_in` in `token_out` by at
+/// least `target_impact`, a fraction between 0 and 1.
+///
+/// Constant product pools are solved in closed form, every other pool is searched
+/// numerically over [`AutomatedMarketMaker::simulate_swap_mut`]. Returns `None` when no
+/// amount reaches the target, like for ERC4626 vaults whose price does not move, or when
+/// the swaps needed fail, like concentrated liquidity pools running past their synced
+/// ticks.
+pub fn amount_for_price_impact(
+    amm: &AMM,
+    token_in: Felt,
+    token_out: Felt,
+    target_impact: f64,
+) -> Result<Option<Amount>, AMMError> {
+    if !(target_impact > 0.0 && target_impact < 1.0) {
+        return Err(AMMError::InvalidPriceImpact(target_impact));
+    }
+    amm.validate_swap_tokens(token_in, token_out)?;
+
+    if let Some((reserve_in, fee)) = constant_product_reserve_in(amm, token_in) {
+        return Ok(constant_product_amount_for_impact(
+            reserve_in,
+            fee,
+            target_impact,
+        ));
+    }
+
+    search_amount_for_impact(amm, token_in, token_out, target_impact)
+}
+
+/// Numerical search of [`amount_for_price_impact`]: doubles the amount until the target is
+/// reached, then bisects between the last two amounts.
+fn search_amount_for_impact(
+    amm: &AMM,
+    token_in: Felt,
+    token_out: Felt,
+    target_impact: f64,
+) -> Result<Option<Amount>, AMMError> {
+    let price_before = amm.calculate_price(token_in, token_out)?;
+    if !price_before.is_finite() || price_before <= 0.0 {
+        return Ok(None);
+    }
+
+    // None when the swap fails, true when it moves the price by at least the target.
+    let reaches = |amount: &BigUint| -> Option<bool> {
+        let amount = Amount::from_biguint(amount).ok()?;
+        let price_after = price_after_swap(amm, token_in, token_out, amount).ok()?;
+        Some(1.0 - price_after / price_before >= target_impact)
+    };
+
+    // Double the amount until the target is reached, `low` never reaches it.
+    let mut low = BigUint::zero();
+    let mut high = BigUint::one();
+    loop {
+        match reaches(&high) {
+            Some(true) => break,
+            Some(false) => {
+                low = high.clone();
+                high <<= 1;
+            }
+            None => return Ok(None),
+        }
+    }
+
+    while &high - &low > BigUint::one() {
+        let mid = (&low + &high) >> 1;
+        if reaches(&mid).unwrap_or(false) {
+            high = mid;
+        } else {
+            low = mid;
+        }
+    }
+
+    Ok(Amount::from_biguint(&high).ok())
+}
+
+/// Reserve of `token_in` and fee of pools whose reserves follow `x * y = k` with the whole
+/// input, fee included, added to the reserves.
+fn constant_product_reserve_in(amm: &AMM, token_in: Felt) -> Option<(Amount, Fee)> {
+    let (token_a, reserve_a, reserve_b, fee) = match amm {
+        AMM::UniswapV2Pool(pool) => (pool.token_a, pool.reserve_a, pool.reserve_b, pool.fee),
+        AMM::MySwapPool(pool) => (pool.token_a, pool.reserve_a, pool.reserve_b, pool.fee),
+        AMM::SithSwapPool(pool) if !pool.stable => {
+            (pool.token_a, pool.reserve_a, pool.reserve_b, pool.fee)
+        }
+        AMM::NostraPool(pool) if !pool.stable => {
+            (pool.token_a, pool.reserve_a, pool.reserve_b, pool.fee)
+        }
+        _ => return None,
+    };
+
+    if token_in == token_a {
+        Some((reserve_a, fee))
+    } else {
+        Some((reserve_b, fee))
+    }
+}
+
+/// Swapping `a` into reserves `(x, y)` with fee complement `g` leaves `(x + a, xy / (x + ga))`,
+/// so the price moves by `x² / ((x + a)(x + ga))`. Setting that to `1 - impact` gives
+/// `ga² + (1 + g)xa - x² impact / (1 - impact) = 0`.
+fn constant_product_amount_for_impact(
+    reserve_in: Amount,
+    fee: Fee,
+    target_impact: f64,
+) -> Option<Amount> {
+    let x = to_f64(reserve_in);
+    let g = 1.0 - fee.to_f64();
+    if x <= 0.0 || g <= 0.0 {
+        return None;
+    }
+
+    // Positive root of the quadratic, written to avoid cancellation for small impacts.
+    let b = (1.0 + g) * x;
+    let c = x * x * target_impact / (1.0 - target_impact);
+    let amount = 2.0 * c / ((b * b + 4.0 * g * c).sqrt() + b);
+
+    Amount::from_biguint(&BigUint::from_f64(amount.ceil())?).ok()
+}
+
+fn to_f64(amount: Amount) -> f64 {
+    amount.to_biguint().to_f64().unwrap_or(f64::INFINITY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amm::{
+        erc4626::pool::ERC4626Vault,
+        uniswap_v2::{descriptor::JEDISWAP, pool::UniswapV2Pool},
+    };
+
+    const E18: u128 = 1_000_000_000_000_000_000;
+    const TOKEN_A: Felt = Felt::from_hex_unchecked("0xa");
+    const TOKEN_B: Felt = Felt::from_hex_unchecked("0xb");
+
+    fn v2_pool() -> AMM {
+        AMM::UniswapV2Pool(UniswapV2Pool::new(
+            Felt::from_hex_unchecked("0x1"),
+            TOKEN_A,
+            TOKEN_B,
+            18,
+            18,
+            Amount::from(1_000 * E18),
+            Amount::from(2_000 * E18),
+            JEDISWAP,
+        ))
+    }
+
+    #[test]
+    fn closed_form_matches_the_numerical_search_on_a_v2_pool() {
+        let amm = v2_pool();
+        for target_impact in [0.001, 0.01, 0.1, 0.5] {
+            let closed_form = amount_for_price_impact(&amm, TOKEN_A, TOKEN_B, target_impact)
+                .unwrap()
+                .unwrap();
+            let searched = search_amount_for_impact(&amm, TOKEN_A, TOKEN_B, target_impact)
+                .unwrap()
+                .unwrap();
+
+            let (closed_form, searched) = (to_f64(closed_form), to_f64(searched));
+            assert!(
+                (closed_form - searched).abs() / searched < 1e-9,
+                "{target_impact}: {closed_form} != {searched}"
+            );
+        }
+    }
+
+    #[test]
+    fn search_returns_the_smallest_amount_reaching_the_target() {
+        let amm = v2_pool();
+        let target_impact = 0.01;
+
+        let amount = search_amount_for_impact(&amm, TOKEN_A, TOKEN_B, target_impact)
+            .unwrap()
+            .unwrap();
+        let below = Amount::from_biguint(&(amount.to_biguint() - BigUint::one())).unwrap();
+
+        assert!(price_impact(&amm, TOKEN_A, TOKEN_B, amount).unwrap() >= target_impact);
+        assert!(price_impact(&amm, TOKEN_A, TOKEN_B, below).unwrap() < target_impact);
+    }
+
+    #[test]
+    fn search_stops_doubling_when_the_price_never_moves() {
+        let vault = AMM::ERC4626Vault(ERC4626Vault::new(
+            TOKEN_A,
+            18,
+            TOKEN_B,
+            18,
+            Amount::from(1_000 * E18),
+            Amount::from(1_100 * E18),
+            Fee::default(),
+            Fee::default(),
+        ));
+
+        assert_eq!(
+            amount_for_price_impact(&vault, TOKEN_A, TOKEN_B, 0.01).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn target_impact_must_be_between_0_and_1() {
+        let amm = v2_pool();
+        for target_impact in [0.0, 1.0, -0.5, f64::NAN] {
+            assert!(matches!(
+                amount_for_price_impact(&amm, TOKEN_A, TOKEN_B, target_impact),
+                Err(AMMError::InvalidPriceImpact(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn price_impact_fails_on_a_zero_spot_price() {
+        let amm = AMM::UniswapV2Pool(UniswapV2Pool::new(
+            Felt::from_hex_unchecked("0x1"),
+            TOKEN_A,
+            TOKEN_B,
+            18,
+            18,
+            Amount::from(1_000 * E18),
+            Amount::ZERO,
+            JEDISWAP,
+        ));
+
+        assert!(matches!(
+            price_impact(&amm, TOKEN_A, TOKEN_B, Amount::from(E18)),
+            Err(AMMError::ZeroSpotPrice(_))
+        ));
+        assert!(matches!(
+            depth_curve(&amm, TOKEN_A, TOKEN_B, &[Amount::from(E18)]),
+            Err(AMMError::ZeroSpotPrice(_))
+        ));
+    }
+}
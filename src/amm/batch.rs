@@ -0,0 +1,358 @@
+use std::{collections::HashMap, ops::Range, sync::Arc};
+
+use starknet::{
+    core::types::{BlockId, Felt, FunctionCall},
+    providers::Provider,
+};
+
+use super::{
+    erc4626,
+    factory::{AutomatedMarketMakerFactory, Factory},
+    myswap,
+    nostra::{self, pool::Nostra},
+    pool::{AutomatedMarketMaker, AMM},
+    sithswap::{self, pool::SithSwap},
+    solidly::{self, pool::SolidlyFork},
+    types::{PoolId, Reserves},
+    uniswap_v2,
+};
+use crate::{
+    errors::AMMError,
+    utils::{batch_call_contracts, felt_to_u8, function_call, MAX_BATCH_SIZE},
+};
+
+/// Outcome of populating many pools, one entry per pool in order.
+#[derive(Debug, Default)]
+pub struct PopulateReport {
+    pub results: Vec<(PoolId, Result<(), AMMError>)>,
+    /// New pools that could not be populated. They have no state yet, so they are held back
+    /// from the synced pools and retried on the next sync.
+    pub unpopulated: Vec<AMM>,
+}
+
+impl PopulateReport {
+    /// Pools that could not be populated.
+    pub fn failed(&self) -> impl Iterator<Item = &PoolId> {
+        self.results
+            .iter()
+            .filter(|(_, result)| result.is_err())
+            .map(|(id, _)| id)
+    }
+
+    /// True when every pool was populated.
+    pub fn is_ok(&self) -> bool {
+        self.results.iter().all(|(_, result)| result.is_ok())
+    }
+
+    pub fn merge(&mut self, other: PopulateReport) {
+        self.results.extend(other.results);
+        self.unpopulated.extend(other.unpopulated);
+    }
+}
+
+/// Populates `amms` at `block_id` with `factory`, or only syncs them when there is none, and
+/// writes the state read back into the pools.
+///
+/// All pools are read at once first. When that fails they are retried [`MAX_BATCH_SIZE`] at a
+/// time, and a failing group is split in halves down to single pools, so that a bad pool only
+/// fails itself: it is left untouched and recorded as [`AMMError::SyncError`].
+pub async fn populate_amms_with_report<P>(
+    factory: Option<&Factory>,
+    amms: &mut [AMM],
+    block_id: BlockId,
+    provider: Arc<P>,
+) -> PopulateReport
+where
+    P: Provider + Send + Sync,
+{
+    let mut results: Vec<Option<Result<(), AMMError>>> = amms.iter().map(|_| None).collect();
+    let mut pending: Vec<Range<usize>> = vec![0..amms.len()];
+
+    while let Some(range) = pending.pop() {
+        if range.is_empty() {
+            continue;
+        }
+
+        let mut populated = amms[range.clone()].to_vec();
+        match populate_with(factory, &mut populated, block_id, provider.clone()).await {
+            Ok(()) => {
+                amms[range.clone()].swap_with_slice(&mut populated);
+                for result in &mut results[range] {
+                    *result = Some(Ok(()));
+                }
+            }
+            Err(_) if range.len() == 1 => {
+                results[range.start] = Some(Err(AMMError::SyncError(amms[range.start].address())));
+            }
+            Err(_) => pending.extend(split(range).rev()),
+        }
+    }
+
+    PopulateReport {
+        results: amms
+            .iter()
+            .zip(results)
+            .map(|(amm, result)| (amm.id(), result.unwrap_or(Ok(()))))
+            .collect(),
+        unpopulated: vec![],
+    }
+}
+
+/// Splits a failed group of pools into [`MAX_BATCH_SIZE`] chunks, or in halves once it fits in
+/// a single batch.
+fn split(range: Range<usize>) -> impl DoubleEndedIterator<Item = Range<usize>> {
+    let size = if range.len() > MAX_BATCH_SIZE {
+        MAX_BATCH_SIZE
+    } else {
+        range.len().div_ceil(2)
+    };
+
+    range
+        .clone()
+        .step_by(size)
+        .map(move |start| start..(start + size).min(range.end))
+}
+
+async fn populate_with<P>(
+    factory: Option<&Factory>,
+    amms: &mut [AMM],
+    block_id: BlockId,
+    provider: Arc<P>,
+) -> Result<(), AMMError>
+where
+    P: Provider + Send + Sync,
+{
+    match factory {
+        Some(factory) => factory.populate_amm_data(amms, block_id, provider).await,
+        None => sync_amms(amms, block_id, provider).await,
+    }
+}
+
+/// Syncs every pool of `amms` to `block_id`.
+///
+/// The reserves of constant product pools and vaults are all read with batched requests.
+/// Concentrated liquidity pools are synced one by one, since their state spans their
+/// initialized ticks.
+pub async fn sync_amms<P>(
+    amms: &mut [AMM],
+    block_id: BlockId,
+    provider: Arc<P>,
+) -> Result<(), AMMError>
+where
+    P: Provider + Send + Sync,
+{
+    let mut calls = vec![];
+    let mut batched = vec![];
+    for (idx, amm) in amms.iter().enumerate() {
+        if let Some(amm_calls) = reserve_calls(amm)? {
+            batched.push((idx, amm_calls.len()));
+            calls.extend(amm_calls);
+        }
+    }
+
+    let mut results = batch_call_contracts(provider.clone(), calls, block_id)
+        .await
+        .map_err(|_| AMMError::BatchRequestError)?
+        .into_iter();
+
+    for (idx, count) in batched {
+        let amm = &mut amms[idx];
+        let amm_results: Vec<Vec<Felt>> = results.by_ref().take(count).collect();
+        let reserves =
+            decode_reserves(amm, &amm_results).ok_or(AMMError::SyncError(amm.address()))?;
+        set_reserves(amm, reserves);
+    }
+
+    for amm in amms.iter_mut() {
+        if matches!(amm, AMM::EkuboPool(_) | AMM::JediswapV2Pool(_)) {
+            amm.sync(block_id, provider.clone())
+                .await
+                .map_err(|_| AMMError::SyncError(amm.address()))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads the tokens, decimals and fee of every Uniswap V2 style, Solidly and mySwap pool of
+/// `amms` at `block_id` with batched requests, then syncs all of `amms` with [`sync_amms`].
+///
+/// Decimals are read once per token however many pools share it.
+pub async fn populate_amms<P>(
+    amms: &mut [AMM],
+    block_id: BlockId,
+    provider: Arc<P>,
+) -> Result<(), AMMError>
+where
+    P: Provider + Send + Sync,
+{
+    let mut calls = vec![];
+    let mut batched = vec![];
+    for (idx, amm) in amms.iter().enumerate() {
+        if let Some(amm_calls) = metadata_calls(amm)? {
+            batched.push((idx, amm_calls.len()));
+            calls.extend(amm_calls);
+        }
+    }
+
+    let mut results = batch_call_contracts(provider.clone(), calls, block_id)
+        .await
+        .map_err(|_| AMMError::BatchRequestError)?
+        .into_iter();
+
+    let mut populated = Vec::with_capacity(batched.len());
+    for (idx, count) in batched {
+        let amm_results: Vec<Vec<Felt>> = results.by_ref().take(count).collect();
+        set_metadata(&mut amms[idx], &amm_results).ok_or(AMMError::PoolDataError)?;
+        populated.push(idx);
+    }
+
+    let mut tokens: Vec<Felt> = populated
+        .iter()
+        .flat_map(|idx| amms[*idx].tokens())
+        .collect();
+    tokens.sort();
+    tokens.dedup();
+
+    let decimals_calls = tokens
+        .iter()
+        .map(|token| call(*token, "decimals", vec![]))
+        .collect::<Result<Vec<_>, _>>()?;
+    let decimals = batch_call_contracts(provider.clone(), decimals_calls, block_id)
+        .await
+        .map_err(|_| AMMError::BatchRequestError)?
+        .into_iter()
+        .map(|result| result.first().copied().map(felt_to_u8))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or(AMMError::PoolDataError)?;
+    let decimals: HashMap<Felt, u8> = tokens.into_iter().zip(decimals).collect();
+
+    for idx in populated {
+        let (token_a, token_b, token_a_decimals, token_b_decimals) = match &mut amms[idx] {
+            AMM::UniswapV2Pool(pool) => (
+                pool.token_a,
+                pool.token_b,
+                &mut pool.token_a_decimals,
+                &mut pool.token_b_decimals,
+            ),
+            AMM::SithSwapPool(pool) => (
+                pool.token_a,
+                pool.token_b,
+                &mut pool.token_a_decimals,
+                &mut pool.token_b_decimals,
+            ),
+            AMM::NostraPool(pool) => (
+                pool.token_a,
+                pool.token_b,
+                &mut pool.token_a_decimals,
+                &mut pool.token_b_decimals,
+            ),
+            AMM::MySwapPool(pool) => (
+                pool.token_a,
+                pool.token_b,
+                &mut pool.token_a_decimals,
+                &mut pool.token_b_decimals,
+            ),
+            _ => continue,
+        };
+        *token_a_decimals = decimals[&token_a];
+        *token_b_decimals = decimals[&token_b];
+    }
+
+    sync_amms(amms, block_id, provider).await
+}
+
+fn call(address: Felt, method: &str, calldata: Vec<Felt>) -> Result<FunctionCall, AMMError> {
+    function_call(address, method, calldata).map_err(|_| AMMError::PoolDataError)
+}
+
+/// Calls returning the metadata of a pool, `None` for pools populated otherwise.
+fn metadata_calls(amm: &AMM) -> Result<Option<Vec<FunctionCall>>, AMMError> {
+    let calls = match amm {
+        AMM::UniswapV2Pool(pool) => uniswap_v2::get_data::metadata_calls(pool.pool_address)?,
+        AMM::SithSwapPool(pool) => sithswap::get_data::metadata_calls(pool.pool_address)?,
+        AMM::NostraPool(pool) => nostra::get_data::metadata_calls(pool.pool_address)?,
+        AMM::MySwapPool(pool) => {
+            myswap::get_data::metadata_calls(pool.contract_address, pool.pool_id)?
+        }
+        AMM::EkuboPool(_) | AMM::JediswapV2Pool(_) | AMM::ERC4626Vault(_) => return Ok(None),
+    };
+
+    Ok(Some(calls))
+}
+
+/// Writes the results of [`metadata_calls`] into `amm`.
+fn set_metadata(amm: &mut AMM, results: &[Vec<Felt>]) -> Option<()> {
+    match amm {
+        AMM::UniswapV2Pool(pool) => uniswap_v2::get_data::set_metadata(pool, results),
+        AMM::SithSwapPool(pool) => sithswap::get_data::set_metadata(pool, results),
+        AMM::NostraPool(pool) => nostra::get_data::set_metadata(pool, results),
+        AMM::MySwapPool(pool) => myswap::get_data::set_metadata(pool, results),
+        AMM::EkuboPool(_) | AMM::JediswapV2Pool(_) | AMM::ERC4626Vault(_) => Some(()),
+    }
+}
+
+/// Calls returning the reserves of a pool, `None` for concentrated liquidity pools.
+fn reserve_calls(amm: &AMM) -> Result<Option<Vec<FunctionCall>>, AMMError> {
+    let calls = match amm {
+        AMM::UniswapV2Pool(pool) => {
+            uniswap_v2::get_data::reserve_calls(pool.pool_address, &pool.descriptor)?
+        }
+        AMM::SithSwapPool(pool) => {
+            solidly::get_data::reserve_calls(pool.pool_address, SithSwap::RESERVES_SELECTOR)?
+        }
+        AMM::NostraPool(pool) => {
+            solidly::get_data::reserve_calls(pool.pool_address, Nostra::RESERVES_SELECTOR)?
+        }
+        AMM::MySwapPool(pool) => {
+            myswap::get_data::reserve_calls(pool.contract_address, pool.pool_id)?
+        }
+        AMM::ERC4626Vault(vault) => erc4626::get_data::reserve_calls(vault.vault_token)?,
+        AMM::EkuboPool(_) | AMM::JediswapV2Pool(_) => return Ok(None),
+    };
+
+    Ok(Some(calls))
+}
+
+/// Decodes the results of [`reserve_calls`].
+fn decode_reserves(amm: &AMM, results: &[Vec<Felt>]) -> Option<Reserves> {
+    match amm {
+        AMM::UniswapV2Pool(pool) => {
+            uniswap_v2::get_data::decode_reserves(&pool.descriptor, results)
+        }
+        AMM::SithSwapPool(_) | AMM::NostraPool(_) => solidly::get_data::decode_reserves(results),
+        AMM::MySwapPool(_) => myswap::get_data::decode_reserves(results),
+        AMM::ERC4626Vault(_) => erc4626::get_data::decode_reserves(results),
+        AMM::EkuboPool(_) | AMM::JediswapV2Pool(_) => None,
+    }
+}
+
+fn set_reserves(amm: &mut AMM, reserves: Reserves) {
+    let Reserves {
+        reserve_a,
+        reserve_b,
+    } = reserves;
+    match amm {
+        AMM::UniswapV2Pool(pool) => {
+            pool.reserve_a = reserve_a;
+            pool.reserve_b = reserve_b;
+        }
+        AMM::SithSwapPool(pool) => {
+            pool.reserve_a = reserve_a;
+            pool.reserve_b = reserve_b;
+        }
+        AMM::NostraPool(pool) => {
+            pool.reserve_a = reserve_a;
+            pool.reserve_b = reserve_b;
+        }
+        AMM::MySwapPool(pool) => {
+            pool.reserve_a = reserve_a;
+            pool.reserve_b = reserve_b;
+        }
+        AMM::ERC4626Vault(vault) => {
+            vault.vault_reserve = reserve_a;
+            vault.asset_reserve = reserve_b;
+        }
+        AMM::EkuboPool(_) | AMM::JediswapV2Pool(_) => {}
+    }
+}
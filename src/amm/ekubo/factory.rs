@@ -0,0 +1,197 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use starknet::{
+    core::types::{BlockId, EmittedEvent, Felt},
+    macros::selector,
+    providers::Provider,
+};
+
+use super::{
+    get_data::get_pool_info,
+    pool::{EkuboPool, PoolKey},
+};
+use crate::{
+    amm::{
+        factory::{get_events_in_range, AutomatedMarketMakerFactory},
+        math::{biguint_to_felt, u256_to_biguint},
+        pool::AMM,
+    },
+    errors::{AMMError, EventLogError},
+    utils::{call_contract, decode_i129, felt_to_u128},
+};
+
+/// Fee tiers looked up for a pair, as `(fee, tick_spacing)` with the fee a 0.128 fixed point
+/// fraction: 0.01%, 0.05%, 0.3% and 1%.
+pub const FEE_TIERS: [(u128, u128); 4] = [
+    (34028236692093847977029636859101184, 200),
+    (170141183460469235273462165868118016, 1000),
+    (1020847100762815411640772995208708096, 5982),
+    (3402823669209384705469243317362360320, 19802),
+];
+
+/// Discovers Ekubo pools from the `PoolInitialized` events of the Core contract.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct EkuboFactory {
+    pub core_address: Felt,
+    pub creation_block: u64,
+}
+
+#[async_trait]
+impl AutomatedMarketMakerFactory for EkuboFactory {
+    fn address(&self) -> Felt {
+        self.core_address
+    }
+
+    async fn fetch_all_pools<P>(&mut self, provider: Arc<P>) -> Result<Vec<AMM>, AMMError>
+    where
+        P: Provider + Sync + Send,
+    {
+        let current_block = provider.block_number().await?;
+        let mut amms = self
+            .get_pools_from_range(
+                self.creation_block,
+                current_block,
+                current_block,
+                provider.clone(),
+            )
+            .await?;
+
+        self.populate_amm_data(&mut amms, BlockId::Number(current_block), provider)
+            .await?;
+
+        Ok(amms)
+    }
+
+    fn amm_created_event_signature(&self) -> Vec<Vec<Felt>> {
+        vec![vec![selector!("PoolInitialized")]]
+    }
+
+    async fn populate_amm_data<P>(
+        &self,
+        amms: &mut [AMM],
+        block_id: BlockId,
+        provider: Arc<P>,
+    ) -> Result<(), AMMError>
+    where
+        P: Provider + Send + Sync,
+    {
+        for amm in amms.iter_mut() {
+            if let AMM::EkuboPool(pool) = amm {
+                *pool = get_pool_info(
+                    pool.core_address,
+                    pool.pool_key.clone(),
+                    block_id,
+                    provider.clone(),
+                )
+                .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl EkuboFactory {
+    pub fn new(core_address: Felt, creation_block: u64) -> EkuboFactory {
+        EkuboFactory {
+            core_address,
+            creation_block,
+        }
+    }
+
+    /// Returns the pool of every fee tier in [`FEE_TIERS`] that is initialized for
+    /// `token_a`/`token_b` without an extension, synced at `block_id`.
+    pub async fn get_pools_for_pair<P>(
+        &self,
+        token_a: Felt,
+        token_b: Felt,
+        block_id: BlockId,
+        provider: Arc<P>,
+    ) -> Result<Vec<AMM>, AMMError>
+    where
+        P: Provider + Send + Sync,
+    {
+        // Pool keys hold their tokens sorted
+        let (token0, token1) = if token_a < token_b {
+            (token_a, token_b)
+        } else {
+            (token_b, token_a)
+        };
+
+        let mut amms = vec![];
+
+        for (fee, tick_spacing) in FEE_TIERS {
+            let pool_key = PoolKey::new(token0, token1, fee, tick_spacing, Felt::ZERO);
+
+            // An uninitialized pool has a zero sqrt_ratio
+            let price = call_contract(
+                provider.clone(),
+                self.core_address,
+                "get_pool_price",
+                pool_key.to_calldata(),
+                block_id,
+            )
+            .await
+            .map_err(|_| AMMError::PoolDataError)?;
+            if price.len() < 2 || (price[0] == Felt::ZERO && price[1] == Felt::ZERO) {
+                continue;
+            }
+
+            amms.push(AMM::EkuboPool(
+                get_pool_info(self.core_address, pool_key, block_id, provider.clone()).await?,
+            ));
+        }
+
+        Ok(amms)
+    }
+
+    /// Returns an unsynced pool for every `PoolInitialized` event emitted in `[from_block, to_block]`.
+    pub async fn get_pools_from_range<P>(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        step: u64,
+        provider: Arc<P>,
+    ) -> Result<Vec<AMM>, AMMError>
+    where
+        P: Provider + Send + Sync,
+    {
+        get_events_in_range(
+            self.core_address,
+            self.amm_created_event_signature(),
+            from_block,
+            to_block,
+            step,
+            provider,
+        )
+        .await?
+        .into_iter()
+        .map(|event| self.new_empty_amm_from_log(event))
+        .collect()
+    }
+
+    /// Builds an unsynced pool from a `PoolInitialized { pool_key, initial_tick, sqrt_ratio }` event.
+    pub fn new_empty_amm_from_log(&self, log: EmittedEvent) -> Result<AMM, AMMError> {
+        if log.keys.first() != Some(&selector!("PoolInitialized")) {
+            return Err(EventLogError::InvalidEventSignature.into());
+        }
+        if log.data.len() < 9 {
+            return Err(AMMError::UnrecognizedPoolCreatedEventLog);
+        }
+
+        let pool_key = PoolKey::new(
+            log.data[0],
+            log.data[1],
+            felt_to_u128(log.data[2]),
+            felt_to_u128(log.data[3]),
+            log.data[4],
+        );
+
+        let mut pool = EkuboPool::new(self.core_address, pool_key, 0, 0);
+        pool.tick = decode_i129(log.data[5], log.data[6]) as i32;
+        pool.sqrt_ratio = biguint_to_felt(&u256_to_biguint(log.data[7], log.data[8]));
+
+        Ok(AMM::EkuboPool(pool))
+    }
+}
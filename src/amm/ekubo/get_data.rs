@@ -0,0 +1,199 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use starknet::{
+    core::types::{BlockId, Felt},
+    providers::Provider,
+};
+
+use super::pool::{EkuboPool, PoolKey, MAX_TICK, MIN_TICK};
+use crate::{
+    amm::math::{biguint_to_felt, u256_to_biguint, TickData},
+    errors::AMMError,
+    utils::{call_contract, decode_i129, encode_i129, felt_to_u128, felt_to_u8},
+};
+
+/// Number of `next_initialized_tick`/`prev_initialized_tick` calls made on each side of the price.
+pub const TICK_SEARCH_STEPS: usize = 10;
+
+/// Number of bitmap words the Core contract may skip per tick search.
+pub const TICK_SEARCH_SKIP_AHEAD: u128 = 5;
+
+pub async fn get_pool_info<P>(
+    core_address: Felt,
+    pool_key: PoolKey,
+    block_id: BlockId,
+    provider: Arc<P>,
+) -> Result<EkuboPool, AMMError>
+where
+    P: Provider + Send + Sync,
+{
+    let token0_decimals = call_contract(
+        provider.clone(),
+        pool_key.token0,
+        "decimals",
+        vec![],
+        block_id,
+    )
+    .await
+    .map_err(|_| AMMError::PoolDataError)?[0];
+
+    let token1_decimals = call_contract(
+        provider.clone(),
+        pool_key.token1,
+        "decimals",
+        vec![],
+        block_id,
+    )
+    .await
+    .map_err(|_| AMMError::PoolDataError)?[0];
+
+    let mut pool = EkuboPool::new(
+        core_address,
+        pool_key,
+        felt_to_u8(token0_decimals),
+        felt_to_u8(token1_decimals),
+    );
+    sync_pool_state(&mut pool, block_id, provider).await?;
+
+    Ok(pool)
+}
+
+/// Reads price, active liquidity and the initialized ticks around the price from the Core contract.
+pub async fn sync_pool_state<P>(
+    pool: &mut EkuboPool,
+    block_id: BlockId,
+    provider: Arc<P>,
+) -> Result<(), AMMError>
+where
+    P: Provider + Send + Sync,
+{
+    let pool_key = pool.pool_key.to_calldata();
+
+    let price = call_contract(
+        provider.clone(),
+        pool.core_address,
+        "get_pool_price",
+        pool_key.clone(),
+        block_id,
+    )
+    .await
+    .map_err(|_| AMMError::SyncError(pool.core_address))?;
+    let sqrt_ratio = u256_to_biguint(price[0], price[1]);
+    let tick = decode_i129(price[2], price[3]) as i32;
+
+    let liquidity = call_contract(
+        provider.clone(),
+        pool.core_address,
+        "get_pool_liquidity",
+        pool_key.clone(),
+        block_id,
+    )
+    .await
+    .map_err(|_| AMMError::SyncError(pool.core_address))?[0];
+
+    let mut ticks = BTreeMap::new();
+
+    let mut upper = tick;
+    for _ in 0..TICK_SEARCH_STEPS {
+        let (next, initialized) = search_initialized_tick(
+            pool,
+            "next_initialized_tick",
+            upper,
+            block_id,
+            provider.clone(),
+        )
+        .await?;
+        if initialized {
+            ticks.insert(
+                next,
+                get_liquidity_net(pool, next, block_id, provider.clone()).await?,
+            );
+        }
+        upper = next;
+        if upper >= MAX_TICK {
+            break;
+        }
+    }
+
+    let mut lower = tick;
+    let mut from = tick;
+    for _ in 0..TICK_SEARCH_STEPS {
+        let (prev, initialized) = search_initialized_tick(
+            pool,
+            "prev_initialized_tick",
+            from,
+            block_id,
+            provider.clone(),
+        )
+        .await?;
+        if initialized {
+            ticks.insert(
+                prev,
+                get_liquidity_net(pool, prev, block_id, provider.clone()).await?,
+            );
+        }
+        lower = prev;
+        from = prev - 1;
+        if lower <= MIN_TICK {
+            break;
+        }
+    }
+
+    pool.sqrt_ratio = biguint_to_felt(&sqrt_ratio);
+    pool.tick = tick;
+    pool.liquidity = felt_to_u128(liquidity);
+    pool.tick_data = TickData {
+        ticks,
+        lower,
+        upper,
+    };
+
+    Ok(())
+}
+
+async fn search_initialized_tick<P>(
+    pool: &EkuboPool,
+    method: &str,
+    from: i32,
+    block_id: BlockId,
+    provider: Arc<P>,
+) -> Result<(i32, bool), AMMError>
+where
+    P: Provider + Send + Sync,
+{
+    let mut calldata = pool.pool_key.to_calldata();
+    calldata.extend(encode_i129(from.into()));
+    calldata.push(Felt::from(TICK_SEARCH_SKIP_AHEAD));
+
+    let result = call_contract(provider, pool.core_address, method, calldata, block_id)
+        .await
+        .map_err(|_| AMMError::NoInitializedTicks)?;
+
+    let tick = decode_i129(result[0], result[1]) as i32;
+    Ok((tick.clamp(MIN_TICK, MAX_TICK), result[2] != Felt::ZERO))
+}
+
+async fn get_liquidity_net<P>(
+    pool: &EkuboPool,
+    tick: i32,
+    block_id: BlockId,
+    provider: Arc<P>,
+) -> Result<i128, AMMError>
+where
+    P: Provider + Send + Sync,
+{
+    let mut calldata = pool.pool_key.to_calldata();
+    calldata.extend(encode_i129(tick.into()));
+
+    let result = call_contract(
+        provider,
+        pool.core_address,
+        "get_pool_tick_liquidity_net",
+        calldata,
+        block_id,
+    )
+    .await
+    .map_err(|_| AMMError::NoLiquidityNet)?;
+
+    Ok(decode_i129(result[0], result[1]))
+}
@@ -0,0 +1,278 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use num_traits::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use starknet::{
+    core::types::{BlockId, BlockTag, Felt, StarknetError},
+    providers::Provider,
+};
+use tracing::instrument;
+
+use super::get_data::{get_pool_info, sync_pool_state};
+use crate::{
+    amm::{
+        math::{
+            biguint_to_felt, felt_to_biguint, ratio_to_f64, simulate_exact_input,
+            simulate_exact_output, sqrt_ratio_price_decimal, virtual_reserves,
+            ConcentratedLiquidityState, SwapResult, TickConfig, TickData,
+        },
+        pool::AutomatedMarketMaker,
+        types::{Amount, Fee, PoolId, Token},
+    },
+    errors::{AMMError, SwapSimulationError},
+};
+
+pub const MIN_TICK: i32 = -88722883;
+pub const MAX_TICK: i32 = 88722883;
+
+/// Ekubo ticks are powers of 1.000001 and sqrt prices are 128.128 fixed point numbers.
+pub const EKUBO_TICK_CONFIG: TickConfig = TickConfig {
+    base_num: 1_000_001,
+    base_den: 1_000_000,
+    resolution: 128,
+    min_tick: MIN_TICK,
+    max_tick: MAX_TICK,
+};
+
+/// Identifies a pool inside the Ekubo Core contract.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PoolKey {
+    pub token0: Felt,
+    pub token1: Felt,
+    /// Fee as a 0.128 fixed point fraction of the input amount.
+    pub fee: u128,
+    pub tick_spacing: u128,
+    pub extension: Felt,
+}
+
+impl PoolKey {
+    pub fn new(
+        token0: Felt,
+        token1: Felt,
+        fee: u128,
+        tick_spacing: u128,
+        extension: Felt,
+    ) -> PoolKey {
+        PoolKey {
+            token0,
+            token1,
+            fee,
+            tick_spacing,
+            extension,
+        }
+    }
+
+    /// Serializes the key as Cairo `PoolKey` calldata.
+    pub fn to_calldata(&self) -> Vec<Felt> {
+        vec![
+            self.token0,
+            self.token1,
+            Felt::from(self.fee),
+            Felt::from(self.tick_spacing),
+            self.extension,
+        ]
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct EkuboPool {
+    pub core_address: Felt,
+    pub pool_key: PoolKey,
+    pub token0_decimals: u8,
+    pub token1_decimals: u8,
+    pub sqrt_ratio: Felt,
+    pub tick: i32,
+    pub liquidity: u128,
+    pub tick_data: TickData,
+}
+
+#[async_trait]
+impl AutomatedMarketMaker for EkuboPool {
+    fn address(&self) -> Felt {
+        self.core_address
+    }
+
+    fn id(&self) -> PoolId {
+        PoolId::new(self.core_address, self.pool_key.to_calldata())
+    }
+
+    fn token_handles(&self) -> Vec<Token> {
+        vec![
+            Token::new(self.pool_key.token0, self.token0_decimals),
+            Token::new(self.pool_key.token1, self.token1_decimals),
+        ]
+    }
+
+    fn reserves(&self) -> Vec<Amount> {
+        let (reserve0, reserve1) = virtual_reserves(
+            self.liquidity,
+            &felt_to_biguint(self.sqrt_ratio),
+            EKUBO_TICK_CONFIG.resolution,
+        );
+        [reserve0, reserve1]
+            .iter()
+            .map(|reserve| Amount::from_biguint(reserve).unwrap_or(Amount::MAX))
+            .collect()
+    }
+
+    #[instrument(skip(self, provider), level = "debug")]
+    async fn sync<P>(&mut self, block_id: BlockId, provider: Arc<P>) -> Result<(), StarknetError>
+    where
+        P: Provider + Send + Sync,
+    {
+        sync_pool_state(self, block_id, provider).await?;
+        tracing::info!(sqrt_ratio = ?self.sqrt_ratio, liquidity = ?self.liquidity, tick = self.tick, "Ekubo sync");
+
+        Ok(())
+    }
+
+    fn calculate_price(&self, base_token: Felt, quote_token: Felt) -> Result<f64, AMMError> {
+        self.validate_swap_tokens(base_token, quote_token)?;
+
+        let sqrt_price = felt_to_biguint(self.sqrt_ratio)
+            .to_f64()
+            .unwrap_or_default()
+            / 2f64.powi(EKUBO_TICK_CONFIG.resolution as i32);
+        let price = sqrt_price
+            * sqrt_price
+            * 10f64.powi(self.token0_decimals as i32 - self.token1_decimals as i32);
+
+        if base_token == self.pool_key.token0 {
+            Ok(price)
+        } else {
+            Ok(ratio_to_f64(1.0, price)?)
+        }
+    }
+
+    fn calculate_price_decimal(
+        &self,
+        base_token: Felt,
+        quote_token: Felt,
+    ) -> Result<Decimal, AMMError> {
+        self.validate_swap_tokens(base_token, quote_token)?;
+
+        sqrt_ratio_price_decimal(
+            &felt_to_biguint(self.sqrt_ratio),
+            EKUBO_TICK_CONFIG.resolution,
+            self.token0_decimals,
+            self.token1_decimals,
+            base_token == self.pool_key.token0,
+        )
+        .map_err(AMMError::from)
+    }
+
+    fn simulate_swap(
+        &self,
+        token_in: Felt,
+        token_out: Felt,
+        amount_in: Amount,
+    ) -> Result<Amount, SwapSimulationError> {
+        self.validate_swap_tokens(token_in, token_out)?;
+        let result = self.swap(token_in, amount_in)?;
+
+        Ok(Amount::from_biguint(&result.amount_out)?)
+    }
+
+    /// Locally simulates a swap in the AMM.
+    /// Mutates the AMM state to the state of the AMM after swapping.
+    /// Returns the amount received for `amount_in` of `token_in`.
+    fn simulate_swap_mut(
+        &mut self,
+        token_in: Felt,
+        token_out: Felt,
+        amount_in: Amount,
+    ) -> Result<Amount, SwapSimulationError> {
+        self.validate_swap_tokens(token_in, token_out)?;
+        let result = self.swap(token_in, amount_in)?;
+
+        self.sqrt_ratio = biguint_to_felt(&result.state.sqrt_ratio);
+        self.tick = result.state.tick;
+        self.liquidity = result.state.liquidity;
+
+        Ok(Amount::from_biguint(&result.amount_out)?)
+    }
+
+    /// Returns the amount of `token_in` needed to receive exactly `amount_out` of
+    /// `token_out`, rounded up, crossing ticks as needed.
+    fn get_amount_in(
+        &self,
+        token_in: Felt,
+        token_out: Felt,
+        amount_out: Amount,
+    ) -> Result<Amount, SwapSimulationError> {
+        self.validate_swap_tokens(token_in, token_out)?;
+
+        let result = simulate_exact_output(
+            &EKUBO_TICK_CONFIG,
+            &self.state(),
+            &self.tick_data,
+            &self.fee().numerator(),
+            &self.fee().denominator(),
+            token_in == self.pool_key.token0,
+            &amount_out.to_biguint(),
+        )?;
+
+        Ok(Amount::from_biguint(&result.amount_in)?)
+    }
+}
+
+impl EkuboPool {
+    pub fn new(
+        core_address: Felt,
+        pool_key: PoolKey,
+        token0_decimals: u8,
+        token1_decimals: u8,
+    ) -> EkuboPool {
+        EkuboPool {
+            core_address,
+            pool_key,
+            token0_decimals,
+            token1_decimals,
+            ..Default::default()
+        }
+    }
+
+    /// Fee of the pool, as encoded in its key.
+    pub fn fee(&self) -> Fee {
+        Fee::X128(self.pool_key.fee)
+    }
+
+    pub async fn new_from_pool_key<P>(
+        core_address: Felt,
+        pool_key: PoolKey,
+        provider: Arc<P>,
+    ) -> Result<Self, AMMError>
+    where
+        P: Provider + Send + Sync,
+    {
+        get_pool_info(
+            core_address,
+            pool_key,
+            BlockId::Tag(BlockTag::Latest),
+            provider,
+        )
+        .await
+    }
+
+    fn state(&self) -> ConcentratedLiquidityState {
+        ConcentratedLiquidityState {
+            sqrt_ratio: felt_to_biguint(self.sqrt_ratio),
+            tick: self.tick,
+            liquidity: self.liquidity,
+        }
+    }
+
+    fn swap(&self, token_in: Felt, amount_in: Amount) -> Result<SwapResult, SwapSimulationError> {
+        simulate_exact_input(
+            &EKUBO_TICK_CONFIG,
+            &self.state(),
+            &self.tick_data,
+            &self.fee().numerator(),
+            &self.fee().denominator(),
+            token_in == self.pool_key.token0,
+            &amount_in.to_biguint(),
+        )
+    }
+}
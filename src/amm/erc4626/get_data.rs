@@ -0,0 +1,181 @@
+use std::sync::Arc;
+
+use num_bigint::BigUint;
+use num_traits::{ToPrimitive, Zero};
+use starknet::{
+    core::types::{BlockId, Felt, FunctionCall},
+    providers::Provider,
+};
+
+use super::pool::{ERC4626Vault, FEE_DENOMINATOR};
+use crate::{
+    amm::{
+        math::u256_to_biguint,
+        types::{Amount, Fee, Reserves},
+    },
+    errors::AMMError,
+    utils::{call_contract, felt_to_u8, function_call},
+};
+
+pub async fn get_vault_info<P>(
+    vault_token: Felt,
+    block_id: BlockId,
+    provider: Arc<P>,
+) -> Result<ERC4626Vault, AMMError>
+where
+    P: Provider + Send + Sync,
+{
+    let asset_token = call_contract(provider.clone(), vault_token, "asset", vec![], block_id)
+        .await
+        .map_err(|_| AMMError::PoolDataError)?[0];
+
+    let vault_token_decimals =
+        call_contract(provider.clone(), vault_token, "decimals", vec![], block_id)
+            .await
+            .map_err(|_| AMMError::PoolDataError)?[0];
+
+    let asset_token_decimals =
+        call_contract(provider.clone(), asset_token, "decimals", vec![], block_id)
+            .await
+            .map_err(|_| AMMError::PoolDataError)?[0];
+
+    let vault_token_decimals = felt_to_u8(vault_token_decimals);
+    let asset_token_decimals = felt_to_u8(asset_token_decimals);
+
+    let deposit_fee = get_fee(
+        vault_token,
+        "convert_to_shares",
+        "preview_deposit",
+        asset_token_decimals,
+        block_id,
+        provider.clone(),
+    )
+    .await?;
+
+    let withdraw_fee = get_fee(
+        vault_token,
+        "convert_to_assets",
+        "preview_redeem",
+        vault_token_decimals,
+        block_id,
+        provider.clone(),
+    )
+    .await?;
+
+    let Reserves {
+        reserve_a: vault_reserve,
+        reserve_b: asset_reserve,
+    } = get_reserves(vault_token, block_id, provider).await?;
+
+    Ok(ERC4626Vault::new(
+        vault_token,
+        vault_token_decimals,
+        asset_token,
+        asset_token_decimals,
+        vault_reserve,
+        asset_reserve,
+        deposit_fee,
+        withdraw_fee,
+    ))
+}
+
+/// Returns the vault share supply as `reserve_a` and the assets it holds as `reserve_b`.
+pub async fn get_reserves<P>(
+    vault_token: Felt,
+    block_id: BlockId,
+    provider: Arc<P>,
+) -> Result<Reserves, AMMError>
+where
+    P: Provider + Send + Sync,
+{
+    let total_supply = call_contract(
+        provider.clone(),
+        vault_token,
+        "total_supply",
+        vec![],
+        block_id,
+    )
+    .await
+    .map_err(|_| AMMError::SyncError(vault_token))?;
+
+    let total_assets = call_contract(provider, vault_token, "total_assets", vec![], block_id)
+        .await
+        .map_err(|_| AMMError::SyncError(vault_token))?;
+
+    decode_reserves(&[total_supply, total_assets]).ok_or(AMMError::SyncError(vault_token))
+}
+
+/// Calls returning the share supply and assets of a vault, decoded by [`decode_reserves`].
+pub fn reserve_calls(vault_token: Felt) -> Result<Vec<FunctionCall>, AMMError> {
+    ["total_supply", "total_assets"]
+        .iter()
+        .map(|method| function_call(vault_token, method, vec![]))
+        .collect::<Result<_, _>>()
+        .map_err(|_| AMMError::PoolDataError)
+}
+
+/// Decodes the share supply as `reserve_a` and the assets as `reserve_b`.
+pub fn decode_reserves(results: &[Vec<Felt>]) -> Option<Reserves> {
+    let (total_supply, total_assets) = (results.first()?, results.get(1)?);
+    if total_supply.len() < 2 || total_assets.len() < 2 {
+        return None;
+    }
+
+    Some(Reserves {
+        reserve_a: Amount::from_u256(total_supply[0], total_supply[1]).ok()?,
+        reserve_b: Amount::from_u256(total_assets[0], total_assets[1]).ok()?,
+    })
+}
+
+/// Derives a fee in basis points from the gap between the fee-less conversion and the
+/// preview of the same amount, which OpenZeppelin style vaults charge fees in.
+async fn get_fee<P>(
+    vault_token: Felt,
+    convert_selector: &str,
+    preview_selector: &str,
+    decimals: u8,
+    block_id: BlockId,
+    provider: Arc<P>,
+) -> Result<Fee, AMMError>
+where
+    P: Provider + Send + Sync,
+{
+    // One whole token, as u256 calldata
+    let amount = vec![Felt::from(10u128.pow(decimals.min(38) as u32)), Felt::ZERO];
+
+    let converted = call_contract(
+        provider.clone(),
+        vault_token,
+        convert_selector,
+        amount.clone(),
+        block_id,
+    )
+    .await
+    .map_err(|_| AMMError::PoolDataError)?;
+
+    let previewed = call_contract(provider, vault_token, preview_selector, amount, block_id)
+        .await
+        .map_err(|_| AMMError::PoolDataError)?;
+
+    if converted.len() < 2 || previewed.len() < 2 {
+        return Err(AMMError::PoolDataError);
+    }
+
+    let converted = u256_to_biguint(converted[0], converted[1]);
+    let previewed = u256_to_biguint(previewed[0], previewed[1]);
+
+    if converted.is_zero() {
+        return Ok(Fee::Bps(0));
+    }
+
+    // A preview above the fee-less conversion would be a negative fee
+    if previewed > converted {
+        return Err(AMMError::InvalidERC4626Fee);
+    }
+
+    ((&converted - &previewed) * BigUint::from(FEE_DENOMINATOR) / &converted)
+        .to_u32()
+        .filter(|fee| *fee < FEE_DENOMINATOR)
+        .map(Fee::Bps)
+        .ok_or(AMMError::InvalidERC4626Fee)
+}
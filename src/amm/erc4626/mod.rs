@@ -0,0 +1,2 @@
+pub mod get_data;
+pub mod pool;
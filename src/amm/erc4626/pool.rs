@@ -0,0 +1,367 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use num_bigint::BigUint;
+use num_traits::{One, ToPrimitive, Zero};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use starknet::{
+    core::types::{BlockId, BlockTag, Felt, StarknetError},
+    providers::Provider,
+};
+use tracing::instrument;
+
+use super::get_data::{get_reserves, get_vault_info};
+use crate::{
+    amm::{
+        math::{div_rounding_up, ratio_to_f64, reserves_price_decimal},
+        pool::AutomatedMarketMaker,
+        types::{Amount, Fee, Reserves, Token},
+    },
+    errors::{AMMError, ArithmeticError, SwapSimulationError},
+};
+
+/// Vault fees are derived in basis points.
+pub const FEE_DENOMINATOR: u32 = 10_000;
+
+/// An ERC4626 vault, such as a liquid staking token, seen as a pool between its asset and
+/// its shares. Depositing swaps the asset for shares and redeeming swaps shares back.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ERC4626Vault {
+    /// The vault contract, which is also the share token.
+    pub vault_token: Felt,
+    pub vault_token_decimals: u8,
+    pub asset_token: Felt,
+    pub asset_token_decimals: u8,
+    /// Total supply of shares.
+    pub vault_reserve: Amount,
+    /// Total assets managed by the vault.
+    pub asset_reserve: Amount,
+    /// Fee charged on deposits.
+    pub deposit_fee: Fee,
+    /// Fee charged on redemptions.
+    pub withdraw_fee: Fee,
+}
+
+#[async_trait]
+impl AutomatedMarketMaker for ERC4626Vault {
+    fn address(&self) -> Felt {
+        self.vault_token
+    }
+
+    fn token_handles(&self) -> Vec<Token> {
+        vec![
+            Token::new(self.vault_token, self.vault_token_decimals),
+            Token::new(self.asset_token, self.asset_token_decimals),
+        ]
+    }
+
+    fn reserves(&self) -> Vec<Amount> {
+        vec![self.vault_reserve, self.asset_reserve]
+    }
+
+    #[instrument(skip(self, provider), level = "debug")]
+    async fn sync<P>(&mut self, block_id: BlockId, provider: Arc<P>) -> Result<(), StarknetError>
+    where
+        P: Provider + Send + Sync,
+    {
+        let Reserves {
+            reserve_a: vault_reserve,
+            reserve_b: asset_reserve,
+        } = get_reserves(self.vault_token, block_id, provider).await?;
+        tracing::info!(
+            ?vault_reserve,
+            ?asset_reserve,
+            address = ?self.address(),
+            "ERC4626 sync"
+        );
+
+        self.vault_reserve = vault_reserve;
+        self.asset_reserve = asset_reserve;
+
+        Ok(())
+    }
+
+    /// Price of `base_token` at the vault exchange rate, without fees.
+    fn calculate_price(&self, base_token: Felt, quote_token: Felt) -> Result<f64, AMMError> {
+        self.validate_swap_tokens(base_token, quote_token)?;
+
+        let (vault_reserve, asset_reserve) = self.pricing_reserves();
+        let vault_reserve = vault_reserve.to_f64().unwrap_or_default()
+            / 10f64.powi(self.vault_token_decimals as i32);
+        let asset_reserve = asset_reserve.to_f64().unwrap_or_default()
+            / 10f64.powi(self.asset_token_decimals as i32);
+
+        let share_price = asset_reserve / vault_reserve;
+
+        if base_token == self.vault_token {
+            Ok(share_price)
+        } else {
+            Ok(ratio_to_f64(1.0, share_price)?)
+        }
+    }
+
+    fn calculate_price_decimal(
+        &self,
+        base_token: Felt,
+        quote_token: Felt,
+    ) -> Result<Decimal, AMMError> {
+        self.validate_swap_tokens(base_token, quote_token)?;
+
+        let (vault_reserve, asset_reserve) = self.pricing_reserves();
+
+        let price = if base_token == self.vault_token {
+            reserves_price_decimal(
+                &vault_reserve,
+                &asset_reserve,
+                self.vault_token_decimals,
+                self.asset_token_decimals,
+            )
+        } else {
+            reserves_price_decimal(
+                &asset_reserve,
+                &vault_reserve,
+                self.asset_token_decimals,
+                self.vault_token_decimals,
+            )
+        };
+
+        price.map_err(AMMError::from)
+    }
+
+    fn simulate_swap(
+        &self,
+        token_in: Felt,
+        token_out: Felt,
+        amount_in: Amount,
+    ) -> Result<Amount, SwapSimulationError> {
+        self.validate_swap_tokens(token_in, token_out)?;
+
+        Ok(self.get_amount_out(amount_in, self.asset_token == token_in)?)
+    }
+
+    /// Locally simulates a swap in the AMM.
+    /// Mutates the AMM state to the state of the AMM after swapping.
+    /// Returns the amount received for `amount_in` of `token_in`.
+    fn simulate_swap_mut(
+        &mut self,
+        token_in: Felt,
+        token_out: Felt,
+        amount_in: Amount,
+    ) -> Result<Amount, SwapSimulationError> {
+        let amount_out = self.simulate_swap(token_in, token_out, amount_in)?;
+
+        if self.asset_token == token_in {
+            self.asset_reserve = self.asset_reserve.checked_add(amount_in)?;
+            self.vault_reserve = self.vault_reserve.checked_add(amount_out)?;
+        } else {
+            self.vault_reserve = self.vault_reserve.checked_sub(amount_in)?;
+            self.asset_reserve = self.asset_reserve.checked_sub(amount_out)?;
+        }
+
+        Ok(amount_out)
+    }
+
+    /// Returns the assets to deposit for exactly `amount_out` shares, or the shares to redeem
+    /// for exactly `amount_out` assets, rounded up.
+    fn get_amount_in(
+        &self,
+        token_in: Felt,
+        token_out: Felt,
+        amount_out: Amount,
+    ) -> Result<Amount, SwapSimulationError> {
+        self.validate_swap_tokens(token_in, token_out)?;
+
+        let is_deposit = self.asset_token == token_in;
+        let amount_out = amount_out.to_biguint();
+        let vault_reserve = self.vault_reserve.to_biguint();
+        let asset_reserve = self.asset_reserve.to_biguint();
+
+        let (reserve_in, reserve_out, fee) = if is_deposit {
+            (asset_reserve, vault_reserve, self.deposit_fee)
+        } else {
+            (vault_reserve, asset_reserve, self.withdraw_fee)
+        };
+
+        // Deposits mint new shares, only redemptions are bounded by the vault assets
+        if !is_deposit && amount_out >= reserve_out {
+            return Err(SwapSimulationError::InsufficientLiquidity);
+        }
+
+        let fee_complement = fee.complement();
+        if fee_complement.is_zero() {
+            return Err(SwapSimulationError::InvalidFee);
+        }
+
+        // Shares of a vault holding no assets are worth nothing
+        if is_deposit && reserve_in.is_zero() && !reserve_out.is_zero() {
+            return Err(ArithmeticError::YIsZero.into());
+        }
+
+        let numerator = amount_out * fee.denominator();
+        let amount_in = if self.vault_reserve.is_zero() {
+            div_rounding_up(&numerator, &fee_complement)
+        } else {
+            div_rounding_up(&(numerator * reserve_in), &(fee_complement * reserve_out))
+        };
+
+        Ok(Amount::from_biguint(&amount_in)?)
+    }
+}
+
+impl ERC4626Vault {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        vault_token: Felt,
+        vault_token_decimals: u8,
+        asset_token: Felt,
+        asset_token_decimals: u8,
+        vault_reserve: Amount,
+        asset_reserve: Amount,
+        deposit_fee: Fee,
+        withdraw_fee: Fee,
+    ) -> ERC4626Vault {
+        ERC4626Vault {
+            vault_token,
+            vault_token_decimals,
+            asset_token,
+            asset_token_decimals,
+            vault_reserve,
+            asset_reserve,
+            deposit_fee,
+            withdraw_fee,
+        }
+    }
+
+    /// Reads the vault asset, decimals, reserves and fees. Fees are derived from
+    /// `preview_deposit`/`preview_redeem` and fail with `InvalidERC4626Fee` when the
+    /// previews do not look like a proportional fee.
+    pub async fn new_from_address<P>(vault_token: Felt, provider: Arc<P>) -> Result<Self, AMMError>
+    where
+        P: Provider + Send + Sync,
+    {
+        get_vault_info(vault_token, BlockId::Tag(BlockTag::Latest), provider).await
+    }
+
+    /// Reserves the price is computed from. A vault without shares mints them one for one in
+    /// raw units, so its price is that of equal raw reserves, scaled by the decimals of each
+    /// token.
+    fn pricing_reserves(&self) -> (BigUint, BigUint) {
+        if self.vault_reserve.is_zero() {
+            return (BigUint::one(), BigUint::one());
+        }
+
+        (
+            self.vault_reserve.to_biguint(),
+            self.asset_reserve.to_biguint(),
+        )
+    }
+
+    /// Shares minted for `amount_in` assets when `is_deposit`, otherwise assets returned for
+    /// redeeming `amount_in` shares. Rounds down like the vault does.
+    ///
+    /// A vault without shares mints them one for one, redeeming from a vault without assets
+    /// returns nothing, and depositing into a vault whose shares hold no assets fails.
+    pub fn get_amount_out(
+        &self,
+        amount_in: Amount,
+        is_deposit: bool,
+    ) -> Result<Amount, ArithmeticError> {
+        let amount_in = amount_in.to_biguint();
+        let vault_reserve = self.vault_reserve.to_biguint();
+        let asset_reserve = self.asset_reserve.to_biguint();
+
+        let (reserve_in, reserve_out, fee) = if is_deposit {
+            (asset_reserve, vault_reserve, self.deposit_fee)
+        } else {
+            (vault_reserve, asset_reserve, self.withdraw_fee)
+        };
+
+        let amount_in_with_fee = amount_in * fee.complement();
+
+        if !is_deposit && reserve_out.is_zero() {
+            return Ok(Amount::ZERO);
+        }
+        if self.vault_reserve.is_zero() {
+            return Amount::from_biguint(&(amount_in_with_fee / fee.denominator()));
+        }
+        if reserve_in.is_zero() {
+            return Err(ArithmeticError::YIsZero);
+        }
+
+        Amount::from_biguint(&(amount_in_with_fee * reserve_out / (reserve_in * fee.denominator())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VAULT: Felt = Felt::from_hex_unchecked("0x5a");
+    const ASSET: Felt = Felt::from_hex_unchecked("0xa5");
+    const E18: u128 = 1_000_000_000_000_000_000;
+
+    fn vault(vault_reserve: u128, asset_reserve: u128) -> ERC4626Vault {
+        ERC4626Vault::new(
+            VAULT,
+            18,
+            ASSET,
+            6,
+            Amount::from(vault_reserve),
+            Amount::from(asset_reserve),
+            Fee::default(),
+            Fee::default(),
+        )
+    }
+
+    #[test]
+    fn redeeming_from_a_vault_without_assets_returns_nothing() {
+        let mut pool = vault(1_000 * E18, 0);
+
+        let amount_out = pool
+            .simulate_swap_mut(VAULT, ASSET, Amount::from(E18))
+            .unwrap();
+
+        assert!(amount_out.is_zero());
+        assert_eq!(pool.vault_reserve, Amount::from(999 * E18));
+        assert!(matches!(
+            pool.get_amount_in(VAULT, ASSET, Amount::from(1u32)),
+            Err(SwapSimulationError::InsufficientLiquidity)
+        ));
+    }
+
+    #[test]
+    fn depositing_into_a_vault_without_assets_fails() {
+        let pool = vault(1_000 * E18, 0);
+
+        assert!(pool
+            .simulate_swap(ASSET, VAULT, Amount::from(1_000_000u32))
+            .is_err());
+        assert!(pool.get_amount_in(ASSET, VAULT, Amount::from(E18)).is_err());
+    }
+
+    #[test]
+    fn a_vault_without_shares_mints_them_one_for_one() {
+        // Assets donated before the first deposit do not change the rate
+        let pool = vault(0, 5_000_000);
+
+        assert_eq!(
+            pool.simulate_swap(ASSET, VAULT, Amount::from(1_000_000u32))
+                .unwrap(),
+            Amount::from(1_000_000u32)
+        );
+        assert_eq!(
+            pool.get_amount_in(ASSET, VAULT, Amount::from(1_000_000u32))
+                .unwrap(),
+            Amount::from(1_000_000u32)
+        );
+
+        // One raw share per raw asset, 1e12 assets per share in whole tokens
+        let price = pool.calculate_price(VAULT, ASSET).unwrap();
+        assert!((price / 1e12 - 1.0).abs() < 1e-12);
+        assert_eq!(
+            pool.calculate_price_decimal(VAULT, ASSET).unwrap(),
+            Decimal::from(1_000_000_000_000u64)
+        );
+    }
+}
@@ -1,15 +1,38 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use futures::stream::FuturesUnordered;
+use futures::{stream, StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use starknet::{
-    core::types::{BlockId, EventFilter, Felt},
+    core::types::{BlockId, EmittedEvent, EventFilter, Felt},
     providers::Provider,
 };
 
-use super::{jediswap::factory::JediswapFactory, pool::AMM, tenkswap::factory::TenKFactory};
-use crate::errors::AMMError;
+use super::{
+    ekubo::factory::EkuboFactory,
+    fetch::{FetchError, FetchOptions},
+    jediswap_v2::factory::JediswapV2Factory,
+    myswap::factory::MySwapFactory,
+    nostra::factory::NostraFactory,
+    pool::AMM,
+    sithswap::factory::SithSwapFactory,
+    uniswap_v2::{factory::UniswapV2Factory, legacy::LegacyV2Factory},
+};
+use crate::errors::{AMMError, EventLogError};
+
+/// Number of events requested per `starknet_getEvents` page.
+pub const EVENTS_CHUNK_SIZE: u64 = 1000;
+
+/// Number of block ranges queried at once when searching for pool creation events.
+pub const MAX_CONCURRENT_EVENT_REQUESTS: usize = 8;
+
+/// An unsynced pool decoded from its creation event.
+#[derive(Debug, Clone)]
+pub struct CreatedPool {
+    pub amm: AMM,
+    /// Block the pool was created at.
+    pub block_number: u64,
+}
 
 #[async_trait]
 pub trait AutomatedMarketMakerFactory {
@@ -22,11 +45,11 @@ pub trait AutomatedMarketMakerFactory {
 
     fn amm_created_event_signature(&self) -> Vec<Vec<Felt>>;
 
-    /// Populates all AMMs data via batched static calls.
+    /// Populates all AMMs data via batched static calls, reading every pool at `block_id`.
     async fn populate_amm_data<P>(
         &self,
         amms: &mut [AMM],
-        block_number: Option<u64>,
+        block_id: BlockId,
         provider: Arc<P>,
     ) -> Result<(), AMMError>
     where
@@ -34,12 +57,32 @@ pub trait AutomatedMarketMakerFactory {
 }
 
 macro_rules! factory {
-    ($($factory_type:ident),+ $(,)?) => {
+    (
+        $($factory_type:ident),+ $(,)?;
+        $($legacy_type:ident($legacy_data:ty) => $convert:path),* $(,)?
+    ) => {
         #[derive(Debug, Clone, Serialize, Deserialize)]
+        #[serde(from = "FactoryRepr")]
         pub enum Factory {
             $($factory_type($factory_type),)+
         }
 
+        /// Serialized form of [`Factory`], which also reads factories of older checkpoints.
+        #[derive(Deserialize)]
+        enum FactoryRepr {
+            $($factory_type($factory_type),)+
+            $($legacy_type($legacy_data),)*
+        }
+
+        impl From<FactoryRepr> for Factory {
+            fn from(repr: FactoryRepr) -> Factory {
+                match repr {
+                    $(FactoryRepr::$factory_type(factory) => Factory::$factory_type(factory),)+
+                    $(FactoryRepr::$legacy_type(factory) => $convert(factory),)*
+                }
+            }
+        }
+
         #[async_trait]
         impl AutomatedMarketMakerFactory for Factory {
             fn address(&self) -> Felt{
@@ -68,7 +111,7 @@ macro_rules! factory {
             async fn populate_amm_data<P>(
                 &self,
                 amms: &mut [AMM],
-                block_number: Option<u64>,
+                block_id: BlockId,
                 provider: Arc<P>,
             ) -> Result<(), AMMError>
             where
@@ -76,7 +119,29 @@ macro_rules! factory {
             {
                 match self {
                     $(Factory::$factory_type(factory) => {
-                        factory.populate_amm_data(amms, block_number, provider).await
+                        factory.populate_amm_data(amms, block_id, provider).await
+                    },)+
+                }
+            }
+        }
+
+
+        impl Factory {
+            /// Returns the pools of the factory trading `token_a` against `token_b`, synced at
+            /// `block_id`, looked up with the factory rather than by enumerating its pools.
+            pub async fn get_pools_for_pair<P>(
+                &self,
+                token_a: Felt,
+                token_b: Felt,
+                block_id: BlockId,
+                provider: Arc<P>,
+            ) -> Result<Vec<AMM>, AMMError>
+            where
+                P: Provider + Send + Sync
+            {
+                match self {
+                    $(Factory::$factory_type(factory) => {
+                        factory.get_pools_for_pair(token_a, token_b, block_id, provider).await
                     },)+
                 }
             }
@@ -93,53 +158,175 @@ macro_rules! factory {
     };
 }
 
-factory!(JediswapFactory, TenKFactory);
+factory!(
+    EkuboFactory,
+    JediswapV2Factory,
+    MySwapFactory,
+    SithSwapFactory,
+    NostraFactory,
+    UniswapV2Factory;
+    JediswapFactory(LegacyV2Factory) => LegacyV2Factory::into_jediswap,
+    TenKFactory(LegacyV2Factory) => LegacyV2Factory::into_tenkswap,
+);
 
 impl Factory {
-    #[allow(unused)]
+    /// Like [`AutomatedMarketMakerFactory::fetch_all_pools`], enumerating pairs with the
+    /// concurrency, rate limit, progress reporting and start index of `options`. Factories
+    /// discovering pools from events or by pool id ignore `options`, so their errors always
+    /// resume from the first pool.
+    pub async fn fetch_all_pools_with<P>(
+        &mut self,
+        options: &FetchOptions,
+        provider: Arc<P>,
+    ) -> Result<Vec<AMM>, FetchError>
+    where
+        P: Provider + Send + Sync,
+    {
+        let factory_address = self.address();
+        match self {
+            Factory::SithSwapFactory(factory) => factory.fetch_pools(options, provider).await,
+            Factory::NostraFactory(factory) => factory.fetch_pools(options, provider).await,
+            Factory::UniswapV2Factory(factory) => factory.fetch_pools(options, provider).await,
+            Factory::EkuboFactory(_)
+            | Factory::JediswapV2Factory(_)
+            | Factory::MySwapFactory(_) => {
+                self.fetch_all_pools(provider)
+                    .await
+                    .map_err(|source| FetchError {
+                        factory: factory_address,
+                        next_index: 0,
+                        pools: vec![],
+                        source,
+                    })
+            }
+        }
+    }
+
+    /// Returns an unsynced pool for every pool created by the factory in
+    /// `[from_block, to_block]`.
+    ///
+    /// The range is queried in chunks of `step` blocks, with at most
+    /// [`MAX_CONCURRENT_EVENT_REQUESTS`] chunks in flight. Pools are returned in creation
+    /// order.
     pub async fn get_all_pools_from_logs<P>(
         &self,
-        mut from_block: u64,
+        from_block: u64,
         to_block: u64,
         step: u64,
         provider: Arc<P>,
     ) -> Result<Vec<AMM>, AMMError>
     where
-        P: Provider,
+        P: Provider + Send + Sync,
     {
+        // mySwap has no creation event, new pools are found by pool id instead
+        if let Factory::MySwapFactory(factory) = self {
+            return factory.get_new_pools(from_block, to_block, provider).await;
+        }
+
         let factory_address = self.address();
         let amm_created_event_signature = self.amm_created_event_signature();
-        let mut futures = FuturesUnordered::new();
+        let step = step.max(1);
 
-        let mut aggregated_amms: Vec<AMM> = vec![];
+        let chunks = (from_block..=to_block)
+            .step_by(step as usize)
+            .map(|chunk_from| {
+                (
+                    chunk_from,
+                    chunk_from.saturating_add(step - 1).min(to_block),
+                )
+            });
 
-        while from_block < to_block {
-            let provider = provider.clone();
-            let mut target_block = from_block + step - 1;
-            if target_block > to_block {
-                target_block = to_block;
-            }
-
-            let filter = EventFilter {
-                from_block: Some(BlockId::Number(from_block)),
-                to_block: Some(BlockId::Number(to_block)),
-                address: Some(factory_address),
-                keys: Some(self.amm_created_event_signature()),
-            };
+        let logs: Vec<Vec<EmittedEvent>> = stream::iter(chunks)
+            .map(|(chunk_from, chunk_to)| {
+                get_events_in_range(
+                    factory_address,
+                    amm_created_event_signature.clone(),
+                    chunk_from,
+                    chunk_to,
+                    step,
+                    provider.clone(),
+                )
+            })
+            .buffered(MAX_CONCURRENT_EVENT_REQUESTS)
+            .try_collect()
+            .await?;
 
-            futures.push(async move { provider.get_events(filter, None, 10).await });
+        logs.into_iter()
+            .flatten()
+            .map(|log| self.new_empty_amm_from_log(log).map(|pool| pool.amm))
+            .collect()
+    }
 
-            from_block += step;
+    /// Builds an unsynced pool from a pool creation event of the factory.
+    ///
+    /// Fails with [`EventLogError::InvalidEventSignature`] for events of another kind,
+    /// [`EventLogError::LogBlockNumberNotFound`] for pending events and
+    /// [`AMMError::UnrecognizedPoolCreatedEventLog`] when the event is missing fields.
+    pub fn new_empty_amm_from_log(&self, log: EmittedEvent) -> Result<CreatedPool, AMMError> {
+        if log.from_address != self.address() {
+            return Err(AMMError::UnrecognizedPoolCreatedEventLog);
         }
+        let block_number = log
+            .block_number
+            .ok_or(EventLogError::LogBlockNumberNotFound)?;
 
-        // while let Some(result) = futures.next().await {
-        //     let logs = result.map_err(AMMError::TransportError)?;
-        //
-        //     for log in logs {
-        //         aggregated_amms.push(self.new_empty_amm_from_log(log).unwrap());
-        //     }
-        // }
+        let amm = match self {
+            Factory::EkuboFactory(factory) => factory.new_empty_amm_from_log(log),
+            Factory::JediswapV2Factory(factory) => factory.new_empty_amm_from_log(log),
+            Factory::SithSwapFactory(factory) => factory.new_empty_amm_from_log(log),
+            Factory::NostraFactory(factory) => factory.new_empty_amm_from_log(log),
+            Factory::UniswapV2Factory(factory) => factory.new_empty_amm_from_log(log),
+            // mySwap pools live in the factory contract and emit no creation event
+            Factory::MySwapFactory(_) => Err(EventLogError::InvalidEventSignature.into()),
+        }?;
 
-        Ok(aggregated_amms)
+        Ok(CreatedPool { amm, block_number })
     }
 }
+
+/// Returns every event emitted by `address` with the given keys in `[from_block, to_block]`,
+/// querying `step` blocks at a time and following continuation tokens.
+pub async fn get_events_in_range<P>(
+    address: Felt,
+    keys: Vec<Vec<Felt>>,
+    mut from_block: u64,
+    to_block: u64,
+    step: u64,
+    provider: Arc<P>,
+) -> Result<Vec<EmittedEvent>, AMMError>
+where
+    P: Provider + Send + Sync,
+{
+    let mut events = vec![];
+
+    while from_block <= to_block {
+        let target_block = from_block.saturating_add(step.max(1) - 1).min(to_block);
+
+        let filter = EventFilter {
+            from_block: Some(BlockId::Number(from_block)),
+            to_block: Some(BlockId::Number(target_block)),
+            address: Some(address),
+            keys: Some(keys.clone()),
+        };
+
+        let mut continuation_token = None;
+        loop {
+            let page = provider
+                .get_events(filter.clone(), continuation_token, EVENTS_CHUNK_SIZE)
+                .await?;
+            events.extend(page.events);
+
+            continuation_token = page.continuation_token;
+            if continuation_token.is_none() {
+                break;
+            }
+        }
+
+        if target_block == to_block {
+            break;
+        }
+        from_block = target_block + 1;
+    }
+
+    Ok(events)
+}
@@ -0,0 +1,298 @@
+use std::{sync::Arc, time::Duration};
+
+use futures::{stream, StreamExt, TryStreamExt};
+use starknet::{
+    core::types::{BlockId, Felt},
+    providers::Provider,
+};
+use thiserror::Error;
+use tokio::{
+    sync::{mpsc::UnboundedSender, Mutex},
+    time::{Interval, MissedTickBehavior},
+};
+
+use super::{
+    factory::AutomatedMarketMakerFactory, pool::AMM, uniswap_v2::descriptor::PairEnumeration,
+};
+use crate::{
+    errors::AMMError,
+    utils::{
+        batch_call_contracts, call_contract, decode_felt_array, felt_to_u128, function_call,
+        MAX_BATCH_SIZE,
+    },
+};
+
+/// How a factory enumerates its pools.
+#[derive(Debug, Clone)]
+pub struct FetchOptions {
+    /// Number of requests in flight at once.
+    pub concurrency: usize,
+    /// Upper bound on the requests started per second, unbounded when `None`. A request is
+    /// either one batch of pair addresses or the population of one chunk of pools.
+    pub requests_per_second: Option<u32>,
+    /// Number of pools populated per request.
+    pub chunk_size: usize,
+    /// Index of the first pair to fetch, to resume an enumeration that failed.
+    pub start_index: u64,
+    /// Receives the progress every time a chunk of pools has been populated.
+    pub progress: Option<UnboundedSender<FetchProgress>>,
+}
+
+impl Default for FetchOptions {
+    fn default() -> FetchOptions {
+        FetchOptions {
+            concurrency: 8,
+            requests_per_second: None,
+            chunk_size: MAX_BATCH_SIZE,
+            start_index: 0,
+            progress: None,
+        }
+    }
+}
+
+impl FetchOptions {
+    pub fn new() -> FetchOptions {
+        FetchOptions::default()
+    }
+
+    pub fn concurrency(mut self, concurrency: usize) -> FetchOptions {
+        self.concurrency = concurrency;
+        self
+    }
+
+    pub fn requests_per_second(mut self, requests_per_second: u32) -> FetchOptions {
+        self.requests_per_second = Some(requests_per_second);
+        self
+    }
+
+    pub fn chunk_size(mut self, chunk_size: usize) -> FetchOptions {
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    pub fn start_index(mut self, start_index: u64) -> FetchOptions {
+        self.start_index = start_index;
+        self
+    }
+
+    pub fn progress(mut self, progress: UnboundedSender<FetchProgress>) -> FetchOptions {
+        self.progress = Some(progress);
+        self
+    }
+}
+
+/// Progress of a factory enumeration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FetchProgress {
+    pub factory: Felt,
+    /// Index of the first pair not fetched yet. Every pair before it has been populated.
+    pub next_index: u64,
+    /// Number of pairs of the factory.
+    pub total: u64,
+}
+
+/// An enumeration that stopped before its last pair.
+///
+/// `pools` holds every pool populated before the failure, in pair order, and resuming from
+/// `next_index` with [`FetchOptions::start_index`] fetches the rest.
+#[derive(Error, Debug)]
+#[error("Could not fetch the pools of factory {factory:#x} from pair index {next_index}: {source}")]
+pub struct FetchError {
+    pub factory: Felt,
+    /// Index of the first pair missing from `pools`.
+    pub next_index: u64,
+    pub pools: Vec<AMM>,
+    pub source: AMMError,
+}
+
+/// Spaces requests out to stay under a requests per second budget.
+struct RateLimiter {
+    interval: Option<Mutex<Interval>>,
+}
+
+impl RateLimiter {
+    fn new(requests_per_second: Option<u32>) -> RateLimiter {
+        let interval = requests_per_second
+            .filter(|requests_per_second| *requests_per_second > 0)
+            .map(|requests_per_second| {
+                let mut interval =
+                    tokio::time::interval(Duration::from_secs(1) / requests_per_second);
+                interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                Mutex::new(interval)
+            });
+
+        RateLimiter { interval }
+    }
+
+    async fn acquire(&self) {
+        if let Some(interval) = &self.interval {
+            interval.lock().await.tick().await;
+        }
+    }
+}
+
+/// Enumerates the pairs of `factory` from `options.start_index` and populates them with
+/// [`AutomatedMarketMakerFactory::populate_amm_data`], all at the latest block.
+///
+/// `new_empty_pool` builds the unsynced pool of a pair address. Pools are returned in pair
+/// order. On failure, the [`FetchError`] holds the pools populated so far and the index to
+/// resume from.
+pub async fn fetch_pools<F, P, N>(
+    factory: &F,
+    enumeration: &PairEnumeration,
+    new_empty_pool: N,
+    options: &FetchOptions,
+    provider: Arc<P>,
+) -> Result<Vec<AMM>, FetchError>
+where
+    F: AutomatedMarketMakerFactory + Sync,
+    P: Provider + Send + Sync,
+    N: Fn(Felt) -> AMM + Send,
+{
+    let factory_address = factory.address();
+    let fetch_error = |next_index, pools, source| FetchError {
+        factory: factory_address,
+        next_index,
+        pools,
+        source,
+    };
+
+    let block_id = match provider.block_number().await {
+        Ok(block_number) => BlockId::Number(block_number),
+        Err(err) => return Err(fetch_error(options.start_index, vec![], err.into())),
+    };
+    let limiter = RateLimiter::new(options.requests_per_second);
+    let concurrency = options.concurrency.max(1);
+    let chunk_size = options.chunk_size.max(1);
+
+    let pairs = get_pair_addresses(
+        factory_address,
+        enumeration,
+        options.start_index,
+        block_id,
+        &limiter,
+        concurrency,
+        provider.clone(),
+    )
+    .await
+    .map_err(|source| fetch_error(options.start_index, vec![], source))?;
+    let total = options.start_index + pairs.len() as u64;
+
+    let mut amms: Vec<AMM> = pairs.into_iter().map(new_empty_pool).collect();
+
+    let limiter = &limiter;
+    let mut populated = stream::iter(amms.chunks_mut(chunk_size))
+        .map(|chunk| {
+            let provider = provider.clone();
+            async move {
+                limiter.acquire().await;
+                factory.populate_amm_data(chunk, block_id, provider).await?;
+                Ok::<_, AMMError>(chunk.len() as u64)
+            }
+        })
+        .buffered(concurrency);
+
+    // Chunks complete in pair order, so every pair before `next_index` has been populated
+    let mut next_index = options.start_index;
+    let mut failure = None;
+    while let Some(result) = populated.next().await {
+        match result {
+            Ok(count) => next_index += count,
+            Err(source) => {
+                failure = Some(source);
+                break;
+            }
+        }
+
+        if let Some(progress) = &options.progress {
+            // A dropped receiver only means nobody is watching
+            let _ = progress.send(FetchProgress {
+                factory: factory_address,
+                next_index,
+                total,
+            });
+        }
+    }
+    drop(populated);
+
+    if let Some(source) = failure {
+        amms.truncate((next_index - options.start_index) as usize);
+        return Err(fetch_error(next_index, amms, source));
+    }
+
+    Ok(amms)
+}
+
+/// Returns the address of every pair from `start_index`, reading indexed pairs with batched
+/// requests.
+async fn get_pair_addresses<P>(
+    factory_address: Felt,
+    enumeration: &PairEnumeration,
+    start_index: u64,
+    block_id: BlockId,
+    limiter: &RateLimiter,
+    concurrency: usize,
+    provider: Arc<P>,
+) -> Result<Vec<Felt>, AMMError>
+where
+    P: Provider + Send + Sync,
+{
+    match enumeration {
+        PairEnumeration::Array { all_pairs } => {
+            limiter.acquire().await;
+            let result = call_contract(provider, factory_address, all_pairs, vec![], block_id)
+                .await
+                .map_err(|_| AMMError::PoolDataError)?;
+            let pairs = decode_felt_array(&result).ok_or(AMMError::PoolDataError)?;
+
+            Ok(pairs.iter().skip(start_index as usize).copied().collect())
+        }
+        PairEnumeration::Indexed {
+            all_pairs_length,
+            all_pairs,
+        } => {
+            limiter.acquire().await;
+            let pairs_length = call_contract(
+                provider.clone(),
+                factory_address,
+                all_pairs_length,
+                vec![],
+                block_id,
+            )
+            .await
+            .map_err(|_| AMMError::PoolDataError)?
+            .first()
+            .copied()
+            .ok_or(AMMError::PoolDataError)?;
+
+            let indices: Vec<u64> = (start_index..felt_to_u128(pairs_length) as u64).collect();
+            let pairs: Vec<Vec<Felt>> = stream::iter(indices.chunks(MAX_BATCH_SIZE))
+                .map(|chunk| {
+                    let provider = provider.clone();
+                    async move {
+                        limiter.acquire().await;
+                        let calls = chunk
+                            .iter()
+                            .map(|idx| {
+                                function_call(factory_address, all_pairs, vec![Felt::from(*idx)])
+                            })
+                            .collect::<Result<Vec<_>, _>>()
+                            .map_err(|_| AMMError::PoolDataError)?;
+
+                        batch_call_contracts(provider, calls, block_id)
+                            .await
+                            .map_err(|_| AMMError::BatchRequestError)?
+                            .into_iter()
+                            .map(|result| result.first().copied())
+                            .collect::<Option<Vec<Felt>>>()
+                            .ok_or(AMMError::PoolDataError)
+                    }
+                })
+                .buffered(concurrency)
+                .try_collect()
+                .await?;
+
+            Ok(pairs.concat())
+        }
+    }
+}
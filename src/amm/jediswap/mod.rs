@@ -1,3 +1 @@
 pub mod factory;
-pub mod get_data;
-pub mod pool;
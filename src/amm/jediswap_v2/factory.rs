@@ -0,0 +1,173 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use starknet::{
+    core::types::{BlockId, EmittedEvent, Felt},
+    macros::selector,
+    providers::Provider,
+};
+
+use super::{get_data::get_pool_info, pool::JediswapV2Pool};
+use crate::{
+    amm::{
+        factory::{get_events_in_range, AutomatedMarketMakerFactory},
+        pool::AMM,
+        types::Fee,
+    },
+    errors::{AMMError, EventLogError},
+    utils::{call_contract, felt_to_u128},
+};
+
+/// Fee tiers enabled on the JediSwap V2 factory, in hundredths of a basis point.
+pub const FEE_TIERS: [u32; 4] = [100, 500, 3000, 10000];
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct JediswapV2Factory {
+    pub factory_address: Felt,
+    pub creation_block: u64,
+}
+
+#[async_trait]
+impl AutomatedMarketMakerFactory for JediswapV2Factory {
+    fn address(&self) -> Felt {
+        self.factory_address
+    }
+
+    async fn fetch_all_pools<P>(&mut self, provider: Arc<P>) -> Result<Vec<AMM>, AMMError>
+    where
+        P: Provider + Sync + Send,
+    {
+        let current_block = provider.block_number().await?;
+        let mut amms = self
+            .get_pools_from_range(
+                self.creation_block,
+                current_block,
+                current_block,
+                provider.clone(),
+            )
+            .await?;
+
+        self.populate_amm_data(&mut amms, BlockId::Number(current_block), provider)
+            .await?;
+
+        Ok(amms)
+    }
+
+    fn amm_created_event_signature(&self) -> Vec<Vec<Felt>> {
+        vec![vec![selector!("PoolCreated")]]
+    }
+
+    async fn populate_amm_data<P>(
+        &self,
+        amms: &mut [AMM],
+        block_id: BlockId,
+        provider: Arc<P>,
+    ) -> Result<(), AMMError>
+    where
+        P: Provider + Send + Sync,
+    {
+        for amm in amms.iter_mut() {
+            if let AMM::JediswapV2Pool(pool) = amm {
+                *pool = get_pool_info(pool.pool_address, block_id, provider.clone()).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl JediswapV2Factory {
+    pub fn new(factory_address: Felt, creation_block: u64) -> JediswapV2Factory {
+        JediswapV2Factory {
+            factory_address,
+            creation_block,
+        }
+    }
+
+    /// Returns an unsynced pool for every `PoolCreated` event emitted in `[from_block, to_block]`.
+    pub async fn get_pools_from_range<P>(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        step: u64,
+        provider: Arc<P>,
+    ) -> Result<Vec<AMM>, AMMError>
+    where
+        P: Provider + Send + Sync,
+    {
+        get_events_in_range(
+            self.factory_address,
+            self.amm_created_event_signature(),
+            from_block,
+            to_block,
+            step,
+            provider,
+        )
+        .await?
+        .into_iter()
+        .map(|event| self.new_empty_amm_from_log(event))
+        .collect()
+    }
+
+    /// Returns the pool of every fee tier that exists for `token_a`/`token_b`, synced at
+    /// `block_id`.
+    pub async fn get_pools_for_pair<P>(
+        &self,
+        token_a: Felt,
+        token_b: Felt,
+        block_id: BlockId,
+        provider: Arc<P>,
+    ) -> Result<Vec<AMM>, AMMError>
+    where
+        P: Provider + Send + Sync,
+    {
+        let mut amms = vec![];
+
+        for fee in FEE_TIERS {
+            let pool_address = call_contract(
+                provider.clone(),
+                self.factory_address,
+                "get_pool",
+                vec![token_a, token_b, Felt::from(fee)],
+                block_id,
+            )
+            .await
+            .map_err(|_| AMMError::PoolDataError)?[0];
+
+            if pool_address != Felt::ZERO {
+                amms.push(AMM::JediswapV2Pool(
+                    get_pool_info(pool_address, block_id, provider.clone()).await?,
+                ));
+            }
+        }
+
+        Ok(amms)
+    }
+
+    /// Builds an unsynced pool from a `PoolCreated { token0, token1, fee, tick_spacing, pool }` event.
+    pub fn new_empty_amm_from_log(&self, log: EmittedEvent) -> Result<AMM, AMMError> {
+        if log.keys.first() != Some(&selector!("PoolCreated")) {
+            return Err(EventLogError::InvalidEventSignature.into());
+        }
+
+        // Members may be emitted as keys or as data depending on how they are annotated
+        let fields: Vec<Felt> = log.keys[1..]
+            .iter()
+            .chain(log.data.iter())
+            .cloned()
+            .collect();
+        if fields.len() < 5 {
+            return Err(AMMError::UnrecognizedPoolCreatedEventLog);
+        }
+
+        Ok(AMM::JediswapV2Pool(JediswapV2Pool::new(
+            fields[4],
+            fields[0],
+            fields[1],
+            0,
+            0,
+            Fee::Pips(felt_to_u128(fields[2]) as u32),
+            felt_to_u128(fields[3]) as u32,
+        )))
+    }
+}
@@ -0,0 +1,194 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use starknet::{
+    core::types::{BlockId, Felt},
+    providers::Provider,
+};
+
+use super::pool::{JediswapV2Pool, MAX_TICK, MIN_TICK};
+use crate::{
+    amm::{
+        math::{biguint_to_felt, u256_to_biguint, TickData},
+        types::Fee,
+    },
+    errors::AMMError,
+    utils::{
+        batch_call_contracts, call_contract, felt_to_i128, felt_to_u128, felt_to_u8, function_call,
+    },
+};
+
+/// Number of tick bitmap words read on each side of the current word.
+pub const TICK_BITMAP_WORDS: i32 = 2;
+
+pub async fn get_pool_info<P>(
+    pool_address: Felt,
+    block_id: BlockId,
+    provider: Arc<P>,
+) -> Result<JediswapV2Pool, AMMError>
+where
+    P: Provider + Send + Sync,
+{
+    let token_0_address = call_contract(
+        provider.clone(),
+        pool_address,
+        "get_token0",
+        vec![],
+        block_id,
+    )
+    .await
+    .map_err(|_| AMMError::PoolDataError)?[0];
+
+    let token_1_address = call_contract(
+        provider.clone(),
+        pool_address,
+        "get_token1",
+        vec![],
+        block_id,
+    )
+    .await
+    .map_err(|_| AMMError::PoolDataError)?[0];
+
+    let token0_decimals = call_contract(
+        provider.clone(),
+        token_0_address,
+        "decimals",
+        vec![],
+        block_id,
+    )
+    .await
+    .map_err(|_| AMMError::PoolDataError)?[0];
+
+    let token1_decimals = call_contract(
+        provider.clone(),
+        token_1_address,
+        "decimals",
+        vec![],
+        block_id,
+    )
+    .await
+    .map_err(|_| AMMError::PoolDataError)?[0];
+
+    let fee = call_contract(provider.clone(), pool_address, "get_fee", vec![], block_id)
+        .await
+        .map_err(|_| AMMError::PoolDataError)?[0];
+
+    let tick_spacing = call_contract(
+        provider.clone(),
+        pool_address,
+        "get_tick_spacing",
+        vec![],
+        block_id,
+    )
+    .await
+    .map_err(|_| AMMError::PoolDataError)?[0];
+
+    let mut pool = JediswapV2Pool::new(
+        pool_address,
+        token_0_address,
+        token_1_address,
+        felt_to_u8(token0_decimals),
+        felt_to_u8(token1_decimals),
+        Fee::Pips(felt_to_u128(fee) as u32),
+        felt_to_u128(tick_spacing) as u32,
+    );
+    sync_pool_state(&mut pool, block_id, provider).await?;
+
+    Ok(pool)
+}
+
+/// Reads price, active liquidity and the initialized ticks of the bitmap words around the price.
+///
+/// Each of the three rounds of reads, pool state, bitmap words and tick infos, is sent as one
+/// batched request.
+pub async fn sync_pool_state<P>(
+    pool: &mut JediswapV2Pool,
+    block_id: BlockId,
+    provider: Arc<P>,
+) -> Result<(), AMMError>
+where
+    P: Provider + Send + Sync,
+{
+    let call = |method: &str, calldata: Vec<Felt>| {
+        function_call(pool.pool_address, method, calldata)
+            .map_err(|_| AMMError::SyncError(pool.pool_address))
+    };
+
+    let state = batch_call_contracts(
+        provider.clone(),
+        vec![
+            call("get_sqrt_price_X96", vec![])?,
+            call("get_tick", vec![])?,
+            call("get_liquidity", vec![])?,
+        ],
+        block_id,
+    )
+    .await
+    .map_err(|_| AMMError::SyncError(pool.pool_address))?;
+    let [sqrt_price, tick, liquidity] = &state[..] else {
+        return Err(AMMError::SyncError(pool.pool_address));
+    };
+    if sqrt_price.len() < 2 || tick.is_empty() || liquidity.is_empty() {
+        return Err(AMMError::SyncError(pool.pool_address));
+    }
+
+    let tick = felt_to_i128(tick[0]) as i32;
+    let tick_spacing = pool.tick_spacing.max(1) as i32;
+    let word = tick.div_euclid(tick_spacing) >> 8;
+    let word_positions: Vec<i32> = (word - TICK_BITMAP_WORDS..=word + TICK_BITMAP_WORDS).collect();
+
+    let bitmaps = batch_call_contracts(
+        provider.clone(),
+        word_positions
+            .iter()
+            .map(|word_pos| call("get_tick_bitmap", vec![Felt::from(*word_pos as i16)]))
+            .collect::<Result<Vec<_>, _>>()?,
+        block_id,
+    )
+    .await
+    .map_err(|_| AMMError::NoInitializedTicks)?;
+
+    let mut initialized_ticks = vec![];
+    for (word_pos, bitmap) in word_positions.iter().zip(&bitmaps) {
+        if bitmap.len() < 2 {
+            return Err(AMMError::NoInitializedTicks);
+        }
+        let bitmap = u256_to_biguint(bitmap[0], bitmap[1]);
+
+        for bit in 0..256 {
+            if bitmap.bit(bit) {
+                initialized_ticks.push(((word_pos << 8) + bit as i32) * tick_spacing);
+            }
+        }
+    }
+
+    let tick_infos = batch_call_contracts(
+        provider.clone(),
+        initialized_ticks
+            .iter()
+            .map(|initialized_tick| call("get_tick_info", vec![Felt::from(*initialized_tick)]))
+            .collect::<Result<Vec<_>, _>>()?,
+        block_id,
+    )
+    .await
+    .map_err(|_| AMMError::NoLiquidityNet)?;
+
+    let mut ticks = BTreeMap::new();
+    for (initialized_tick, tick_info) in initialized_ticks.into_iter().zip(tick_infos) {
+        let liquidity_net = tick_info.get(1).ok_or(AMMError::NoLiquidityNet)?;
+        ticks.insert(initialized_tick, felt_to_i128(*liquidity_net));
+    }
+
+    let lower = ((word - TICK_BITMAP_WORDS) << 8) * tick_spacing;
+    let upper = (((word + TICK_BITMAP_WORDS) << 8) + 255) * tick_spacing;
+
+    pool.sqrt_price = biguint_to_felt(&u256_to_biguint(sqrt_price[0], sqrt_price[1]));
+    pool.tick = tick;
+    pool.liquidity = felt_to_u128(liquidity[0]);
+    pool.tick_data = TickData {
+        ticks,
+        lower: lower.max(MIN_TICK),
+        upper: upper.min(MAX_TICK),
+    };
+
+    Ok(())
+}
@@ -0,0 +1,228 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use num_traits::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use starknet::{
+    core::types::{BlockId, BlockTag, Felt, StarknetError},
+    providers::Provider,
+};
+use tracing::instrument;
+
+use super::get_data::{get_pool_info, sync_pool_state};
+use crate::{
+    amm::{
+        math::{
+            biguint_to_felt, felt_to_biguint, ratio_to_f64, simulate_exact_input,
+            simulate_exact_output, sqrt_ratio_price_decimal, virtual_reserves,
+            ConcentratedLiquidityState, SwapResult, TickConfig, TickData,
+        },
+        pool::AutomatedMarketMaker,
+        types::{Amount, Fee, Token},
+    },
+    errors::{AMMError, SwapSimulationError},
+};
+
+pub const MIN_TICK: i32 = -887272;
+pub const MAX_TICK: i32 = 887272;
+
+/// Uniswap V3 ticks are powers of 1.0001 and sqrt prices are Q64.96 numbers.
+pub const UNISWAP_V3_TICK_CONFIG: TickConfig = TickConfig {
+    base_num: 10_001,
+    base_den: 10_000,
+    resolution: 96,
+    min_tick: MIN_TICK,
+    max_tick: MAX_TICK,
+};
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct JediswapV2Pool {
+    pub pool_address: Felt,
+    pub token_a: Felt,
+    pub token_b: Felt,
+    pub token_a_decimals: u8,
+    pub token_b_decimals: u8,
+    pub fee: Fee,
+    pub tick_spacing: u32,
+    pub sqrt_price: Felt,
+    pub tick: i32,
+    pub liquidity: u128,
+    pub tick_data: TickData,
+}
+
+#[async_trait]
+impl AutomatedMarketMaker for JediswapV2Pool {
+    fn address(&self) -> Felt {
+        self.pool_address
+    }
+
+    fn token_handles(&self) -> Vec<Token> {
+        vec![
+            Token::new(self.token_a, self.token_a_decimals),
+            Token::new(self.token_b, self.token_b_decimals),
+        ]
+    }
+
+    fn reserves(&self) -> Vec<Amount> {
+        let (reserve0, reserve1) = virtual_reserves(
+            self.liquidity,
+            &felt_to_biguint(self.sqrt_price),
+            UNISWAP_V3_TICK_CONFIG.resolution,
+        );
+        [reserve0, reserve1]
+            .iter()
+            .map(|reserve| Amount::from_biguint(reserve).unwrap_or(Amount::MAX))
+            .collect()
+    }
+
+    #[instrument(skip(self, provider), level = "debug")]
+    async fn sync<P>(&mut self, block_id: BlockId, provider: Arc<P>) -> Result<(), StarknetError>
+    where
+        P: Provider + Send + Sync,
+    {
+        sync_pool_state(self, block_id, provider).await?;
+        tracing::info!(sqrt_price = ?self.sqrt_price, liquidity = ?self.liquidity, tick = self.tick, address = ?self.address(), "UniswapV3 sync");
+
+        Ok(())
+    }
+
+    fn calculate_price(&self, base_token: Felt, quote_token: Felt) -> Result<f64, AMMError> {
+        self.validate_swap_tokens(base_token, quote_token)?;
+
+        let sqrt_price = felt_to_biguint(self.sqrt_price)
+            .to_f64()
+            .unwrap_or_default()
+            / 2f64.powi(UNISWAP_V3_TICK_CONFIG.resolution as i32);
+        let price = sqrt_price
+            * sqrt_price
+            * 10f64.powi(self.token_a_decimals as i32 - self.token_b_decimals as i32);
+
+        if base_token == self.token_a {
+            Ok(price)
+        } else {
+            Ok(ratio_to_f64(1.0, price)?)
+        }
+    }
+
+    fn calculate_price_decimal(
+        &self,
+        base_token: Felt,
+        quote_token: Felt,
+    ) -> Result<Decimal, AMMError> {
+        self.validate_swap_tokens(base_token, quote_token)?;
+
+        sqrt_ratio_price_decimal(
+            &felt_to_biguint(self.sqrt_price),
+            UNISWAP_V3_TICK_CONFIG.resolution,
+            self.token_a_decimals,
+            self.token_b_decimals,
+            base_token == self.token_a,
+        )
+        .map_err(AMMError::from)
+    }
+
+    fn simulate_swap(
+        &self,
+        token_in: Felt,
+        token_out: Felt,
+        amount_in: Amount,
+    ) -> Result<Amount, SwapSimulationError> {
+        self.validate_swap_tokens(token_in, token_out)?;
+        let result = self.swap(token_in, amount_in)?;
+
+        Ok(Amount::from_biguint(&result.amount_out)?)
+    }
+
+    /// Locally simulates a swap in the AMM.
+    /// Mutates the AMM state to the state of the AMM after swapping.
+    /// Returns the amount received for `amount_in` of `token_in`.
+    fn simulate_swap_mut(
+        &mut self,
+        token_in: Felt,
+        token_out: Felt,
+        amount_in: Amount,
+    ) -> Result<Amount, SwapSimulationError> {
+        self.validate_swap_tokens(token_in, token_out)?;
+        let result = self.swap(token_in, amount_in)?;
+
+        self.sqrt_price = biguint_to_felt(&result.state.sqrt_ratio);
+        self.tick = result.state.tick;
+        self.liquidity = result.state.liquidity;
+
+        Ok(Amount::from_biguint(&result.amount_out)?)
+    }
+
+    /// Returns the amount of `token_in` needed to receive exactly `amount_out` of
+    /// `token_out`, rounded up, crossing ticks as needed.
+    fn get_amount_in(
+        &self,
+        token_in: Felt,
+        token_out: Felt,
+        amount_out: Amount,
+    ) -> Result<Amount, SwapSimulationError> {
+        self.validate_swap_tokens(token_in, token_out)?;
+
+        let result = simulate_exact_output(
+            &UNISWAP_V3_TICK_CONFIG,
+            &self.state(),
+            &self.tick_data,
+            &self.fee.numerator(),
+            &self.fee.denominator(),
+            token_in == self.token_a,
+            &amount_out.to_biguint(),
+        )?;
+
+        Ok(Amount::from_biguint(&result.amount_in)?)
+    }
+}
+
+impl JediswapV2Pool {
+    pub fn new(
+        pool_address: Felt,
+        token_a: Felt,
+        token_b: Felt,
+        token_a_decimals: u8,
+        token_b_decimals: u8,
+        fee: Fee,
+        tick_spacing: u32,
+    ) -> JediswapV2Pool {
+        JediswapV2Pool {
+            pool_address,
+            token_a,
+            token_b,
+            token_a_decimals,
+            token_b_decimals,
+            fee,
+            tick_spacing,
+            ..Default::default()
+        }
+    }
+
+    pub async fn new_from_address<P>(pool_address: Felt, provider: Arc<P>) -> Result<Self, AMMError>
+    where
+        P: Provider + Send + Sync,
+    {
+        get_pool_info(pool_address, BlockId::Tag(BlockTag::Latest), provider).await
+    }
+
+    fn state(&self) -> ConcentratedLiquidityState {
+        ConcentratedLiquidityState {
+            sqrt_ratio: felt_to_biguint(self.sqrt_price),
+            tick: self.tick,
+            liquidity: self.liquidity,
+        }
+    }
+
+    fn swap(&self, token_in: Felt, amount_in: Amount) -> Result<SwapResult, SwapSimulationError> {
+        simulate_exact_input(
+            &UNISWAP_V3_TICK_CONFIG,
+            &self.state(),
+            &self.tick_data,
+            &self.fee.numerator(),
+            &self.fee.denominator(),
+            token_in == self.token_a,
+            &amount_in.to_biguint(),
+        )
+    }
+}
@@ -0,0 +1,200 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use futures::future::join_all;
+use starknet::{
+    core::types::{BlockId, Felt},
+    providers::Provider,
+};
+use thiserror::Error;
+use tokio::{
+    sync::watch,
+    task::JoinHandle,
+    time::{self, MissedTickBehavior},
+};
+
+use super::{
+    factory::{AutomatedMarketMakerFactory, Factory},
+    pool::{AutomatedMarketMaker, AMM},
+    types::Amount,
+};
+use crate::{errors::AMMError, utils::call_contract};
+
+/// A factory on which a pair could not be looked up.
+#[derive(Error, Debug)]
+#[error("Could not look {token_a:#x}/{token_b:#x} up on factory {factory:#x}: {source}")]
+pub struct PairLookupError {
+    pub factory: Felt,
+    pub token_a: Felt,
+    pub token_b: Felt,
+    pub source: AMMError,
+}
+
+/// Returns every pool trading `token_a` against `token_b` on `factories`, populated at the
+/// latest block, without enumerating the factories, along with the factories whose lookup
+/// failed. Pools with an empty reserve cannot be swapped through and are left out.
+///
+/// Fails with [`AMMError::PairDoesNotExistInDexes`] when every factory was looked up and none
+/// has a pool holding both tokens.
+pub async fn get_pair<P>(
+    factories: &[Factory],
+    token_a: Felt,
+    token_b: Felt,
+    provider: Arc<P>,
+) -> Result<(Vec<AMM>, Vec<PairLookupError>), AMMError>
+where
+    P: Provider + Send + Sync,
+{
+    let block_id = BlockId::Number(provider.block_number().await?);
+    get_pair_at(factories, token_a, token_b, block_id, provider).await
+}
+
+/// Like [`get_pair`], reading every factory at `block_id`.
+pub async fn get_pair_at<P>(
+    factories: &[Factory],
+    token_a: Felt,
+    token_b: Felt,
+    block_id: BlockId,
+    provider: Arc<P>,
+) -> Result<(Vec<AMM>, Vec<PairLookupError>), AMMError>
+where
+    P: Provider + Send + Sync,
+{
+    let results =
+        join_all(factories.iter().map(|factory| {
+            factory.get_pools_for_pair(token_a, token_b, block_id, provider.clone())
+        }))
+        .await;
+
+    let mut amms = vec![];
+    let mut errors = vec![];
+    for (factory, result) in factories.iter().zip(results) {
+        match result {
+            Ok(pools) => amms.extend(
+                pools
+                    .into_iter()
+                    .filter(|amm| !amm.reserves().iter().any(Amount::is_zero)),
+            ),
+            Err(source) => errors.push(PairLookupError {
+                factory: factory.address(),
+                token_a,
+                token_b,
+                source,
+            }),
+        }
+    }
+
+    if amms.is_empty() && errors.is_empty() {
+        return Err(AMMError::PairDoesNotExistInDexes(token_a, token_b));
+    }
+
+    Ok((amms, errors))
+}
+
+/// Calls the pair lookup `method` of a factory, `None` when it returns the zero address.
+pub async fn get_pair_address<P>(
+    factory_address: Felt,
+    method: &str,
+    calldata: Vec<Felt>,
+    block_id: BlockId,
+    provider: Arc<P>,
+) -> Result<Option<Felt>, AMMError>
+where
+    P: Provider + Send + Sync,
+{
+    let pair = call_contract(provider, factory_address, method, calldata, block_id)
+        .await
+        .map_err(|_| AMMError::PoolDataError)?;
+    let pair = *pair.first().ok_or(AMMError::PoolDataError)?;
+
+    Ok((pair != Felt::ZERO).then_some(pair))
+}
+
+/// Pools of every watched pair, pairs without pools mapping to no pool.
+pub type PairPools = HashMap<(Felt, Felt), Vec<AMM>>;
+
+/// A list of token pairs whose pools are looked up on a set of factories.
+#[derive(Debug, Clone)]
+pub struct PairWatchlist {
+    pub factories: Vec<Factory>,
+    pub pairs: Vec<(Felt, Felt)>,
+}
+
+impl PairWatchlist {
+    pub fn new(factories: Vec<Factory>, pairs: Vec<(Felt, Felt)>) -> PairWatchlist {
+        PairWatchlist { factories, pairs }
+    }
+
+    /// Looks every pair up at the latest block. A factory failing for a pair only leaves its
+    /// pools out of that pair, and is returned along with the pools.
+    pub async fn lookup<P>(
+        &self,
+        provider: Arc<P>,
+    ) -> Result<(PairPools, Vec<PairLookupError>), AMMError>
+    where
+        P: Provider + Send + Sync,
+    {
+        let block_id = BlockId::Number(provider.block_number().await?);
+
+        let mut pools = HashMap::new();
+        let mut errors = vec![];
+        for &(token_a, token_b) in &self.pairs {
+            let amms = match get_pair_at(
+                &self.factories,
+                token_a,
+                token_b,
+                block_id,
+                provider.clone(),
+            )
+            .await
+            {
+                Ok((amms, pair_errors)) => {
+                    errors.extend(pair_errors);
+                    amms
+                }
+                Err(AMMError::PairDoesNotExistInDexes(_, _)) => vec![],
+                Err(err) => return Err(err),
+            };
+            pools.insert((token_a, token_b), amms);
+        }
+
+        Ok((pools, errors))
+    }
+
+    /// Spawns a task looking every pair up again every `interval`, so that new pools and
+    /// fresh state are published on the returned channel. A lookup that fails, entirely or on
+    /// some factories, is logged and retried at the next interval. The task stops once every
+    /// receiver is dropped.
+    pub fn spawn<P>(
+        self,
+        interval: Duration,
+        provider: Arc<P>,
+    ) -> (JoinHandle<()>, watch::Receiver<PairPools>)
+    where
+        P: Provider + Send + Sync + 'static,
+    {
+        let (sender, receiver) = watch::channel(PairPools::new());
+
+        let handle = tokio::spawn(async move {
+            let mut ticker = time::interval(interval);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            loop {
+                ticker.tick().await;
+
+                match self.lookup(provider.clone()).await {
+                    Ok((pools, errors)) => {
+                        for err in errors {
+                            tracing::warn!(?err, "Pair watchlist lookup failed on a factory");
+                        }
+                        if sender.send(pools).is_err() {
+                            break;
+                        }
+                    }
+                    Err(err) => tracing::warn!(?err, "Pair watchlist lookup failed"),
+                }
+            }
+        });
+
+        (handle, receiver)
+    }
+}
@@ -0,0 +1,1342 @@
+use std::collections::BTreeMap;
+
+use num_bigint::BigUint;
+use num_traits::{One, ToPrimitive, Zero};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use starknet::core::types::Felt;
+
+use crate::{
+    amm::types::Fee,
+    errors::{ArithmeticError, SwapSimulationError},
+};
+
+/// Fractional bits carried while raising the tick base to a power.
+const TICK_MATH_PRECISION: u32 = 192;
+
+/// Largest number of fractional digits a `Decimal` can hold.
+const DECIMAL_MAX_SCALE: u32 = 28;
+
+/// Bits of the `Decimal` mantissa.
+const DECIMAL_MANTISSA_BITS: u32 = 96;
+
+/// Describes how ticks map to sqrt prices for a concentrated liquidity AMM.
+#[derive(Debug, Clone, Copy)]
+pub struct TickConfig {
+    /// Numerator of the price ratio between two adjacent ticks.
+    pub base_num: u64,
+    /// Denominator of the price ratio between two adjacent ticks.
+    pub base_den: u64,
+    /// Number of fractional bits of the sqrt price (96 for Uniswap V3, 128 for Ekubo).
+    pub resolution: u32,
+    pub min_tick: i32,
+    pub max_tick: i32,
+}
+
+/// Initialized ticks of a pool and the tick range they were synced over.
+///
+/// Ticks outside of `[lower, upper]` are unknown, so a simulation that runs past
+/// them fails instead of assuming there is no liquidity.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct TickData {
+    /// Liquidity net of every initialized tick in the synced range.
+    pub ticks: BTreeMap<i32, i128>,
+    pub lower: i32,
+    pub upper: i32,
+}
+
+/// Price state of a concentrated liquidity pool.
+#[derive(Debug, Clone)]
+pub struct ConcentratedLiquidityState {
+    pub sqrt_ratio: BigUint,
+    pub tick: i32,
+    pub liquidity: u128,
+}
+
+#[derive(Debug, Clone)]
+pub struct SwapResult {
+    pub amount_in: BigUint,
+    pub amount_out: BigUint,
+    /// State of the pool after the swap.
+    pub state: ConcentratedLiquidityState,
+}
+
+pub fn felt_to_biguint(value: Felt) -> BigUint {
+    BigUint::from_bytes_be(&value.to_bytes_be())
+}
+
+pub fn biguint_to_felt(value: &BigUint) -> Felt {
+    Felt::from_bytes_be_slice(&value.to_bytes_be())
+}
+
+/// Decodes a Cairo `u256` from its low and high limbs.
+pub fn u256_to_biguint(low: Felt, high: Felt) -> BigUint {
+    (felt_to_biguint(high) << 128u32) + felt_to_biguint(low)
+}
+
+/// Integer division rounding up.
+pub fn div_rounding_up(numerator: &BigUint, denominator: &BigUint) -> BigUint {
+    (numerator + denominator - BigUint::one()) / denominator
+}
+
+/// Converts `numerator / denominator` to a `Decimal`, keeping as many fractional digits as
+/// the 96 bit mantissa allows.
+pub fn ratio_to_decimal(
+    numerator: &BigUint,
+    denominator: &BigUint,
+) -> Result<Decimal, ArithmeticError> {
+    if denominator.is_zero() {
+        return Err(ArithmeticError::YIsZero);
+    }
+
+    let max_mantissa = BigUint::one() << DECIMAL_MANTISSA_BITS;
+    for scale in (0..=DECIMAL_MAX_SCALE).rev() {
+        let mantissa = numerator * BigUint::from(10u32).pow(scale) / denominator;
+        if mantissa < max_mantissa {
+            // Below 2⁹⁶, so the conversion cannot fail
+            return Ok(Decimal::from_i128_with_scale(
+                mantissa.to_i128().unwrap_or_default(),
+                scale,
+            ));
+        }
+    }
+
+    Err(ArithmeticError::DecimalOverflow)
+}
+
+/// Computes `numerator / denominator` as a `f64`, failing like [`ratio_to_decimal`] when
+/// `denominator` is zero instead of returning an infinite or NaN price.
+pub fn ratio_to_f64(numerator: f64, denominator: f64) -> Result<f64, ArithmeticError> {
+    if denominator == 0.0 {
+        return Err(ArithmeticError::YIsZero);
+    }
+
+    Ok(numerator / denominator)
+}
+
+/// Price of the base token in quote tokens from the pool reserves, adjusted for decimals.
+pub fn reserves_price_decimal(
+    reserve_base: &BigUint,
+    reserve_quote: &BigUint,
+    decimals_base: u8,
+    decimals_quote: u8,
+) -> Result<Decimal, ArithmeticError> {
+    ratio_to_decimal(
+        &(reserve_quote * BigUint::from(10u32).pow(decimals_base.into())),
+        &(reserve_base * BigUint::from(10u32).pow(decimals_quote.into())),
+    )
+}
+
+/// Virtual reserves `(L / √P, L * √P)` of the active liquidity of a concentrated liquidity
+/// pool, from a fixed point sqrt price with `resolution` fractional bits.
+pub fn virtual_reserves(
+    liquidity: u128,
+    sqrt_ratio: &BigUint,
+    resolution: u32,
+) -> (BigUint, BigUint) {
+    if sqrt_ratio.is_zero() {
+        return (BigUint::zero(), BigUint::zero());
+    }
+
+    let liquidity = BigUint::from(liquidity);
+    (
+        (&liquidity << resolution) / sqrt_ratio,
+        (liquidity * sqrt_ratio) >> resolution,
+    )
+}
+
+/// Price of token0 in token1 from a fixed point sqrt price with `resolution` fractional bits,
+/// adjusted for decimals. The price of token1 is the inverse.
+pub fn sqrt_ratio_price_decimal(
+    sqrt_ratio: &BigUint,
+    resolution: u32,
+    decimals0: u8,
+    decimals1: u8,
+    zero_for_one: bool,
+) -> Result<Decimal, ArithmeticError> {
+    let numerator = sqrt_ratio * sqrt_ratio * BigUint::from(10u32).pow(decimals0.into());
+    let denominator =
+        (BigUint::one() << (2 * resolution)) * BigUint::from(10u32).pow(decimals1.into());
+
+    if zero_for_one {
+        ratio_to_decimal(&numerator, &denominator)
+    } else {
+        ratio_to_decimal(&denominator, &numerator)
+    }
+}
+
+/// Returns the sqrt price at `tick` with `config.resolution` fractional bits.
+pub fn sqrt_ratio_at_tick(tick: i32, config: &TickConfig) -> BigUint {
+    let mut base = ((BigUint::from(config.base_num) << (2 * TICK_MATH_PRECISION))
+        / BigUint::from(config.base_den))
+    .sqrt();
+    let mut result = BigUint::one() << TICK_MATH_PRECISION;
+    let mut exponent = tick.unsigned_abs();
+
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = (&result * &base) >> TICK_MATH_PRECISION;
+        }
+        base = (&base * &base) >> TICK_MATH_PRECISION;
+        exponent >>= 1;
+    }
+
+    if tick < 0 {
+        result = (BigUint::one() << (2 * TICK_MATH_PRECISION)) / result;
+    }
+
+    result >> (TICK_MATH_PRECISION - config.resolution)
+}
+
+/// Returns the tick whose price is closest below `sqrt_ratio`.
+pub fn tick_at_sqrt_ratio(sqrt_ratio: &BigUint, config: &TickConfig) -> i32 {
+    let sqrt_price = sqrt_ratio.to_f64().unwrap_or(f64::MAX) / 2f64.powi(config.resolution as i32);
+    let base = config.base_num as f64 / config.base_den as f64;
+    let tick = (2.0 * sqrt_price.ln() / base.ln()).floor();
+
+    (tick as i32).clamp(config.min_tick, config.max_tick)
+}
+
+/// Amount of token0 between two sqrt prices for the given liquidity.
+pub fn amount0_delta(
+    sqrt_ratio_a: &BigUint,
+    sqrt_ratio_b: &BigUint,
+    liquidity: u128,
+    resolution: u32,
+    round_up: bool,
+) -> BigUint {
+    let (lower, upper) = if sqrt_ratio_a < sqrt_ratio_b {
+        (sqrt_ratio_a, sqrt_ratio_b)
+    } else {
+        (sqrt_ratio_b, sqrt_ratio_a)
+    };
+
+    if lower.is_zero() {
+        return BigUint::zero();
+    }
+
+    let numerator = (BigUint::from(liquidity) << resolution) * (upper - lower);
+    let denominator = upper * lower;
+
+    if round_up {
+        div_rounding_up(&numerator, &denominator)
+    } else {
+        numerator / denominator
+    }
+}
+
+/// Amount of token1 between two sqrt prices for the given liquidity.
+pub fn amount1_delta(
+    sqrt_ratio_a: &BigUint,
+    sqrt_ratio_b: &BigUint,
+    liquidity: u128,
+    resolution: u32,
+    round_up: bool,
+) -> BigUint {
+    let (lower, upper) = if sqrt_ratio_a < sqrt_ratio_b {
+        (sqrt_ratio_a, sqrt_ratio_b)
+    } else {
+        (sqrt_ratio_b, sqrt_ratio_a)
+    };
+
+    let numerator = BigUint::from(liquidity) * (upper - lower);
+    let denominator = BigUint::one() << resolution;
+
+    if round_up {
+        div_rounding_up(&numerator, &denominator)
+    } else {
+        numerator / denominator
+    }
+}
+
+/// Sqrt price after adding `amount` of token0, rounded up so the price never moves too far.
+pub fn next_sqrt_ratio_from_amount0(
+    sqrt_ratio: &BigUint,
+    liquidity: u128,
+    amount: &BigUint,
+    resolution: u32,
+) -> BigUint {
+    if amount.is_zero() {
+        return sqrt_ratio.clone();
+    }
+
+    let numerator = BigUint::from(liquidity) << resolution;
+    let denominator = &numerator + amount * sqrt_ratio;
+
+    div_rounding_up(&(numerator * sqrt_ratio), &denominator)
+}
+
+/// Sqrt price after adding `amount` of token1, rounded down so the price never moves too far.
+pub fn next_sqrt_ratio_from_amount1(
+    sqrt_ratio: &BigUint,
+    liquidity: u128,
+    amount: &BigUint,
+    resolution: u32,
+) -> BigUint {
+    sqrt_ratio + (amount << resolution) / BigUint::from(liquidity)
+}
+
+/// Sqrt price after removing `amount` of token0, rounded up so the price moves far enough.
+///
+/// `amount` must be less than the token0 available above the current price.
+pub fn next_sqrt_ratio_from_amount0_output(
+    sqrt_ratio: &BigUint,
+    liquidity: u128,
+    amount: &BigUint,
+    resolution: u32,
+) -> BigUint {
+    if amount.is_zero() {
+        return sqrt_ratio.clone();
+    }
+
+    let numerator = BigUint::from(liquidity) << resolution;
+    let denominator = &numerator - amount * sqrt_ratio;
+
+    div_rounding_up(&(numerator * sqrt_ratio), &denominator)
+}
+
+/// Sqrt price after removing `amount` of token1, rounded down so the price moves far enough.
+///
+/// `amount` must be less than the token1 available below the current price.
+pub fn next_sqrt_ratio_from_amount1_output(
+    sqrt_ratio: &BigUint,
+    liquidity: u128,
+    amount: &BigUint,
+    resolution: u32,
+) -> BigUint {
+    sqrt_ratio - div_rounding_up(&(amount << resolution), &BigUint::from(liquidity))
+}
+
+/// Swaps within a single tick range, moving the price from `sqrt_ratio_current` towards
+/// `sqrt_ratio_target`. The fee is `fee / fee_denominator` of the input amount.
+///
+/// Returns the new sqrt price, the input consumed including fees and the output produced.
+pub fn compute_swap_step(
+    sqrt_ratio_current: &BigUint,
+    sqrt_ratio_target: &BigUint,
+    liquidity: u128,
+    amount_remaining: &BigUint,
+    fee: &BigUint,
+    fee_denominator: &BigUint,
+    resolution: u32,
+) -> (BigUint, BigUint, BigUint) {
+    if liquidity == 0 {
+        return (sqrt_ratio_target.clone(), BigUint::zero(), BigUint::zero());
+    }
+
+    let zero_for_one = sqrt_ratio_current >= sqrt_ratio_target;
+    let amount_less_fee = amount_remaining * (fee_denominator - fee) / fee_denominator;
+
+    let amount_to_target = if zero_for_one {
+        amount0_delta(
+            sqrt_ratio_target,
+            sqrt_ratio_current,
+            liquidity,
+            resolution,
+            true,
+        )
+    } else {
+        amount1_delta(
+            sqrt_ratio_current,
+            sqrt_ratio_target,
+            liquidity,
+            resolution,
+            true,
+        )
+    };
+
+    let (sqrt_ratio_next, amount_in) = if amount_less_fee >= amount_to_target {
+        (sqrt_ratio_target.clone(), amount_to_target)
+    } else if zero_for_one {
+        (
+            next_sqrt_ratio_from_amount0(
+                sqrt_ratio_current,
+                liquidity,
+                &amount_less_fee,
+                resolution,
+            ),
+            amount_less_fee,
+        )
+    } else {
+        (
+            next_sqrt_ratio_from_amount1(
+                sqrt_ratio_current,
+                liquidity,
+                &amount_less_fee,
+                resolution,
+            ),
+            amount_less_fee,
+        )
+    };
+
+    let amount_out = if zero_for_one {
+        amount1_delta(
+            &sqrt_ratio_next,
+            sqrt_ratio_current,
+            liquidity,
+            resolution,
+            false,
+        )
+    } else {
+        amount0_delta(
+            sqrt_ratio_current,
+            &sqrt_ratio_next,
+            liquidity,
+            resolution,
+            false,
+        )
+    };
+
+    let amount_in_with_fee = if sqrt_ratio_next == *sqrt_ratio_target {
+        &amount_in + div_rounding_up(&(&amount_in * fee), &(fee_denominator - fee))
+    } else {
+        amount_remaining.clone()
+    };
+
+    (
+        sqrt_ratio_next,
+        amount_in_with_fee.min(amount_remaining.clone()),
+        amount_out,
+    )
+}
+
+/// Swaps within a single tick range until `amount_remaining` of output is produced or the
+/// price reaches `sqrt_ratio_target`. The fee is `fee / fee_denominator` of the input amount.
+///
+/// Returns the new sqrt price, the input required including fees, rounded up, and the output
+/// produced.
+pub fn compute_swap_step_exact_output(
+    sqrt_ratio_current: &BigUint,
+    sqrt_ratio_target: &BigUint,
+    liquidity: u128,
+    amount_remaining: &BigUint,
+    fee: &BigUint,
+    fee_denominator: &BigUint,
+    resolution: u32,
+) -> (BigUint, BigUint, BigUint) {
+    if liquidity == 0 {
+        return (sqrt_ratio_target.clone(), BigUint::zero(), BigUint::zero());
+    }
+
+    let zero_for_one = sqrt_ratio_current >= sqrt_ratio_target;
+
+    let amount_to_target = if zero_for_one {
+        amount1_delta(
+            sqrt_ratio_target,
+            sqrt_ratio_current,
+            liquidity,
+            resolution,
+            false,
+        )
+    } else {
+        amount0_delta(
+            sqrt_ratio_current,
+            sqrt_ratio_target,
+            liquidity,
+            resolution,
+            false,
+        )
+    };
+
+    let (sqrt_ratio_next, amount_out) = if *amount_remaining >= amount_to_target {
+        (sqrt_ratio_target.clone(), amount_to_target)
+    } else if zero_for_one {
+        (
+            next_sqrt_ratio_from_amount1_output(
+                sqrt_ratio_current,
+                liquidity,
+                amount_remaining,
+                resolution,
+            ),
+            amount_remaining.clone(),
+        )
+    } else {
+        (
+            next_sqrt_ratio_from_amount0_output(
+                sqrt_ratio_current,
+                liquidity,
+                amount_remaining,
+                resolution,
+            ),
+            amount_remaining.clone(),
+        )
+    };
+
+    let amount_in = if zero_for_one {
+        amount0_delta(
+            &sqrt_ratio_next,
+            sqrt_ratio_current,
+            liquidity,
+            resolution,
+            true,
+        )
+    } else {
+        amount1_delta(
+            sqrt_ratio_current,
+            &sqrt_ratio_next,
+            liquidity,
+            resolution,
+            true,
+        )
+    };
+
+    let amount_in_with_fee =
+        &amount_in + div_rounding_up(&(&amount_in * fee), &(fee_denominator - fee));
+
+    (sqrt_ratio_next, amount_in_with_fee, amount_out)
+}
+
+/// Simulates an exact input swap across initialized ticks.
+///
+/// Crossing a tick going up adds its liquidity net, crossing it going down subtracts it.
+/// Fails with `InsufficientLiquidity` when the price reaches the end of the tick range before
+/// all of `amount_in` is swapped.
+pub fn simulate_exact_input(
+    config: &TickConfig,
+    state: &ConcentratedLiquidityState,
+    tick_data: &TickData,
+    fee: &BigUint,
+    fee_denominator: &BigUint,
+    zero_for_one: bool,
+    amount_in: &BigUint,
+) -> Result<SwapResult, SwapSimulationError> {
+    simulate_swap(
+        config,
+        state,
+        tick_data,
+        fee,
+        fee_denominator,
+        zero_for_one,
+        amount_in,
+        true,
+    )
+}
+
+/// Simulates an exact output swap across initialized ticks.
+///
+/// Fails with `InsufficientLiquidity` when the pool cannot produce `amount_out` before the
+/// price reaches the end of the tick range.
+pub fn simulate_exact_output(
+    config: &TickConfig,
+    state: &ConcentratedLiquidityState,
+    tick_data: &TickData,
+    fee: &BigUint,
+    fee_denominator: &BigUint,
+    zero_for_one: bool,
+    amount_out: &BigUint,
+) -> Result<SwapResult, SwapSimulationError> {
+    simulate_swap(
+        config,
+        state,
+        tick_data,
+        fee,
+        fee_denominator,
+        zero_for_one,
+        amount_out,
+        false,
+    )
+}
+
+/// Swaps across initialized ticks until `amount` of input, or of output when not
+/// `exact_input`, has been swapped.
+#[allow(clippy::too_many_arguments)]
+fn simulate_swap(
+    config: &TickConfig,
+    state: &ConcentratedLiquidityState,
+    tick_data: &TickData,
+    fee: &BigUint,
+    fee_denominator: &BigUint,
+    zero_for_one: bool,
+    amount: &BigUint,
+    exact_input: bool,
+) -> Result<SwapResult, SwapSimulationError> {
+    if tick_data.ticks.is_empty() && state.liquidity == 0 {
+        return Err(SwapSimulationError::NoInitializedTicks);
+    }
+
+    let mut state = state.clone();
+    let mut amount_remaining = amount.clone();
+    let mut amount_in = BigUint::zero();
+    let mut amount_out = BigUint::zero();
+
+    while !amount_remaining.is_zero() {
+        let next_tick = if zero_for_one {
+            tick_data.ticks.range(..=state.tick).next_back()
+        } else {
+            tick_data.ticks.range(state.tick + 1..).next()
+        };
+
+        let (target_tick, liquidity_net) = match next_tick {
+            Some((tick, liquidity_net)) => (*tick, Some(*liquidity_net)),
+            None if zero_for_one => (tick_data.lower.max(config.min_tick), None),
+            None => (tick_data.upper.min(config.max_tick), None),
+        };
+        let sqrt_ratio_target = sqrt_ratio_at_tick(target_tick, config);
+
+        let step = if exact_input {
+            compute_swap_step
+        } else {
+            compute_swap_step_exact_output
+        };
+        let (sqrt_ratio_next, step_in, step_out) = step(
+            &state.sqrt_ratio,
+            &sqrt_ratio_target,
+            state.liquidity,
+            &amount_remaining,
+            fee,
+            fee_denominator,
+            config.resolution,
+        );
+
+        if exact_input {
+            amount_remaining -= &step_in;
+        } else {
+            amount_remaining -= &step_out;
+        }
+        amount_in += step_in;
+        amount_out += step_out;
+
+        if sqrt_ratio_next != sqrt_ratio_target {
+            // Keep the tick inside the range that has not been crossed yet
+            let (low, high) = if zero_for_one {
+                (target_tick, state.tick)
+            } else {
+                (state.tick, target_tick - 1)
+            };
+            state.tick = tick_at_sqrt_ratio(&sqrt_ratio_next, config)
+                .max(low)
+                .min(high);
+            state.sqrt_ratio = sqrt_ratio_next;
+            continue;
+        }
+
+        state.sqrt_ratio = sqrt_ratio_next;
+        match liquidity_net {
+            Some(liquidity_net) => {
+                let liquidity = if zero_for_one {
+                    state.liquidity as i128 - liquidity_net
+                } else {
+                    state.liquidity as i128 + liquidity_net
+                };
+                if liquidity < 0 {
+                    return Err(SwapSimulationError::LiquidityUnderflow);
+                }
+
+                state.liquidity = liquidity as u128;
+                state.tick = if zero_for_one {
+                    target_tick - 1
+                } else {
+                    target_tick
+                };
+            }
+            None => {
+                state.tick = target_tick;
+                if amount_remaining.is_zero() {
+                    break;
+                }
+                if target_tick == config.min_tick || target_tick == config.max_tick {
+                    // The pool cannot take or produce what is left
+                    return Err(SwapSimulationError::InsufficientLiquidity);
+                }
+
+                // The price left the synced tick range
+                return Err(SwapSimulationError::InvalidTick);
+            }
+        }
+    }
+
+    Ok(SwapResult {
+        amount_in,
+        amount_out,
+        state,
+    })
+}
+
+/// Output of a constant product swap, as computed by Uniswap V2 style pairs with the fee
+/// taken from the input: `a·(d - f)·y / (x·d + a·(d - f))` for a fee of `f / d`. With
+/// `Fee::Bps(30)` this is exactly the pairs' `a·997·y / (x·1000 + a·997)`.
+pub fn constant_product_amount_out(
+    amount_in: &BigUint,
+    reserve_in: &BigUint,
+    reserve_out: &BigUint,
+    fee: Fee,
+) -> BigUint {
+    if amount_in.is_zero() || reserve_in.is_zero() || reserve_out.is_zero() {
+        return BigUint::zero();
+    }
+
+    let amount_in_with_fee = amount_in * fee.complement();
+    let numerator = &amount_in_with_fee * reserve_out;
+    let denominator = reserve_in * fee.denominator() + amount_in_with_fee;
+
+    numerator / denominator
+}
+
+/// Input needed to receive `amount_out` from a constant product swap, as computed by the pairs'
+/// `get_amount_in`: `x·b·d / ((y - b)·(d - f)) + 1` for a fee of `f / d`, which always
+/// rounds up.
+pub fn constant_product_amount_in(
+    amount_out: &BigUint,
+    reserve_in: &BigUint,
+    reserve_out: &BigUint,
+    fee: Fee,
+) -> Result<BigUint, SwapSimulationError> {
+    if amount_out >= reserve_out {
+        return Err(SwapSimulationError::InsufficientLiquidity);
+    }
+    if amount_out.is_zero() {
+        return Ok(BigUint::zero());
+    }
+
+    let numerator = reserve_in * amount_out * fee.denominator();
+    let denominator = (reserve_out - amount_out) * fee.complement();
+    if denominator.is_zero() {
+        return Err(SwapSimulationError::InvalidFee);
+    }
+
+    Ok(numerator / denominator + BigUint::one())
+}
+
+/// Fixed point scale used by the Solidly stable invariant.
+const SOLIDLY_PRECISION: u64 = 1_000_000_000_000_000_000;
+
+/// Maximum number of Newton iterations used to solve the Solidly stable invariant.
+const SOLIDLY_MAX_ITERATIONS: usize = 255;
+
+fn solidly_f(x0: &BigUint, y: &BigUint, one: &BigUint) -> BigUint {
+    x0 * (y * y / one * y / one) / one + (x0 * x0 / one * x0 / one) * y / one
+}
+
+fn solidly_d(x0: &BigUint, y: &BigUint, one: &BigUint) -> BigUint {
+    BigUint::from(3u32) * x0 * (y * y / one) / one + (x0 * x0 / one * x0 / one)
+}
+
+/// Solves `x0³y + x0y³ = xy` for `y` with Newton's method, starting from `y`.
+fn solidly_get_y(x0: &BigUint, xy: &BigUint, mut y: BigUint, one: &BigUint) -> BigUint {
+    for _ in 0..SOLIDLY_MAX_ITERATIONS {
+        let y_prev = y.clone();
+        let k = solidly_f(x0, &y, one);
+        let d = solidly_d(x0, &y, one);
+        if d.is_zero() {
+            break;
+        }
+
+        if k < *xy {
+            y += (xy - &k) * one / &d;
+        } else {
+            let dy = (&k - xy) * one / &d;
+            y = if dy > y { BigUint::zero() } else { y - dy };
+        }
+
+        let delta = if y > y_prev {
+            &y - &y_prev
+        } else {
+            &y_prev - &y
+        };
+        if delta <= BigUint::one() {
+            break;
+        }
+    }
+
+    y
+}
+
+/// Output amount of a Solidly style pair, `x³y + y³x = k` when `stable` and `xy = k` otherwise.
+///
+/// `fee` is taken from the input amount. Stable reserves are normalised to 18 decimals before
+/// solving the invariant.
+pub fn solidly_get_amount_out(
+    amount_in: &BigUint,
+    reserve_in: &BigUint,
+    reserve_out: &BigUint,
+    decimals_in: u8,
+    decimals_out: u8,
+    fee: Fee,
+    stable: bool,
+) -> BigUint {
+    if amount_in.is_zero()
+        || reserve_in.is_zero()
+        || reserve_out.is_zero()
+        || fee.complement().is_zero()
+    {
+        return BigUint::zero();
+    }
+
+    let amount_in = amount_in - amount_in * fee.numerator() / fee.denominator();
+
+    if !stable {
+        return &amount_in * reserve_out / (reserve_in + &amount_in);
+    }
+
+    let one = BigUint::from(SOLIDLY_PRECISION);
+    let scale_in = BigUint::from(10u32).pow(decimals_in.into());
+    let scale_out = BigUint::from(10u32).pow(decimals_out.into());
+
+    let reserve_in = reserve_in * &one / &scale_in;
+    let reserve_out = reserve_out * &one / &scale_out;
+    let amount_in = amount_in * &one / &scale_in;
+
+    let a = &reserve_in * &reserve_out / &one;
+    let b = &reserve_in * &reserve_in / &one + &reserve_out * &reserve_out / &one;
+    let xy = a * b / &one;
+
+    let y = solidly_get_y(&(amount_in + &reserve_in), &xy, reserve_out.clone(), &one);
+    if y >= reserve_out {
+        return BigUint::zero();
+    }
+
+    (reserve_out - y) * scale_out / one
+}
+
+/// Input amount needed to receive `amount_out` from a Solidly style pair, rounded up at every
+/// step so that swapping it yields at least `amount_out`.
+pub fn solidly_get_amount_in(
+    amount_out: &BigUint,
+    reserve_in: &BigUint,
+    reserve_out: &BigUint,
+    decimals_in: u8,
+    decimals_out: u8,
+    fee: Fee,
+    stable: bool,
+) -> Result<BigUint, SwapSimulationError> {
+    if amount_out >= reserve_out {
+        return Err(SwapSimulationError::InsufficientLiquidity);
+    }
+    if amount_out.is_zero() {
+        return Ok(BigUint::zero());
+    }
+
+    let amount_in = if stable {
+        let one = BigUint::from(SOLIDLY_PRECISION);
+        let scale_in = BigUint::from(10u32).pow(decimals_in.into());
+        let scale_out = BigUint::from(10u32).pow(decimals_out.into());
+
+        let reserve_in = reserve_in * &one / &scale_in;
+        let reserve_out = reserve_out * &one / &scale_out;
+        let amount_out = div_rounding_up(&(amount_out * &one), &scale_out);
+        if amount_out >= reserve_out {
+            return Err(SwapSimulationError::InsufficientLiquidity);
+        }
+
+        let a = &reserve_in * &reserve_out / &one;
+        let b = &reserve_in * &reserve_in / &one + &reserve_out * &reserve_out / &one;
+        let xy = a * b / &one;
+
+        // The invariant is symmetric, so the input reserve is solved like the output one
+        let x = solidly_get_y(&(&reserve_out - amount_out), &xy, reserve_in.clone(), &one);
+        if x <= reserve_in {
+            return Ok(BigUint::one());
+        }
+
+        div_rounding_up(&((x - reserve_in) * scale_in), &one) + BigUint::one()
+    } else {
+        div_rounding_up(&(reserve_in * amount_out), &(reserve_out - amount_out))
+    };
+
+    let fee_complement = fee.complement();
+    if fee_complement.is_zero() {
+        return Err(SwapSimulationError::InvalidFee);
+    }
+
+    Ok(div_rounding_up(
+        &(amount_in * fee.denominator()),
+        &fee_complement,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const E18: u128 = 1_000_000_000_000_000_000;
+
+    fn big(value: u128) -> BigUint {
+        BigUint::from(value)
+    }
+
+    #[test]
+    fn constant_product_amount_out_matches_pair_formula() {
+        // a·997·y / (x·1000 + a·997) = 997e18·200e18 / 100997e18
+        let amount_out =
+            constant_product_amount_out(&big(E18), &big(100 * E18), &big(200 * E18), Fee::Bps(30));
+        assert_eq!(amount_out, big(1_974_316_068_794_122_597));
+
+        let amount_out = constant_product_amount_out(
+            &big(1_000),
+            &big(1_000_000),
+            &big(1_000_000),
+            Fee::Bps(30),
+        );
+        assert_eq!(amount_out, big(996));
+    }
+
+    #[test]
+    fn constant_product_amount_out_is_zero_without_input_or_reserves() {
+        let fee = Fee::Bps(30);
+        assert!(constant_product_amount_out(&big(0), &big(10), &big(10), fee).is_zero());
+        assert!(constant_product_amount_out(&big(10), &big(0), &big(10), fee).is_zero());
+        assert!(constant_product_amount_out(&big(10), &big(10), &big(0), fee).is_zero());
+    }
+
+    #[test]
+    fn constant_product_amount_in_matches_pair_formula() {
+        // x·b·1000 / ((y - b)·997) + 1 = 100e18·1e18·1000 / (199e18·997) + 1
+        let amount_in =
+            constant_product_amount_in(&big(E18), &big(100 * E18), &big(200 * E18), Fee::Bps(30))
+                .unwrap();
+        assert_eq!(amount_in, big(504_024_636_724_243_082));
+    }
+
+    #[test]
+    fn constant_product_amount_in_rounds_up() {
+        let (reserve_in, reserve_out, fee) = (big(1_000_000), big(1_000_000), Fee::Bps(30));
+
+        let amount_in =
+            constant_product_amount_in(&big(996), &reserve_in, &reserve_out, fee).unwrap();
+        assert_eq!(amount_in, big(1_000));
+
+        // The input is enough for the requested output, one unit less is not
+        assert!(
+            constant_product_amount_out(&amount_in, &reserve_in, &reserve_out, fee) >= big(996)
+        );
+        assert!(
+            constant_product_amount_out(&(amount_in - 1u32), &reserve_in, &reserve_out, fee)
+                < big(996)
+        );
+
+        // Like the pair, one is added even when the division is exact: 997·1·1000 / (1·997)
+        let amount_in = constant_product_amount_in(&big(1), &big(997), &big(2), fee).unwrap();
+        assert_eq!(amount_in, big(1_001));
+    }
+
+    #[test]
+    fn constant_product_amount_in_fails_without_enough_reserve() {
+        let result = constant_product_amount_in(&big(10), &big(10), &big(10), Fee::Bps(30));
+        assert!(matches!(
+            result,
+            Err(SwapSimulationError::InsufficientLiquidity)
+        ));
+
+        let amount_in = constant_product_amount_in(&big(0), &big(10), &big(10), Fee::Bps(30));
+        assert!(amount_in.unwrap().is_zero());
+    }
+
+    #[test]
+    fn prices_fail_the_same_way_on_an_empty_reserve() {
+        assert!(matches!(
+            ratio_to_f64(1.0, 0.0),
+            Err(ArithmeticError::YIsZero)
+        ));
+        assert!(matches!(
+            reserves_price_decimal(&big(0), &big(E18), 18, 18),
+            Err(ArithmeticError::YIsZero)
+        ));
+
+        assert_eq!(ratio_to_f64(0.0, 2.0).unwrap(), 0.0);
+        assert!(reserves_price_decimal(&big(E18), &big(0), 18, 18)
+            .unwrap()
+            .is_zero());
+    }
+
+    #[test]
+    fn ekubo_sqrt_ratios_match_known_ticks() {
+        use crate::amm::ekubo::pool::{EKUBO_TICK_CONFIG, MAX_TICK};
+
+        // floor(sqrt(1.000001^tick)·2^128)
+        let known = [
+            (0, "340282366920938463463374607431768211456"),
+            (1, "340282537062079388658008856451427006220"),
+            (-1, "340282196779882608775400081051345954874"),
+            (100_000, "357729008007184124257033863759518583669"),
+            (-100_000, "323686608146104804031747792844693073975"),
+        ];
+        for (tick, sqrt_ratio) in known {
+            assert_eq!(
+                sqrt_ratio_at_tick(tick, &EKUBO_TICK_CONFIG),
+                sqrt_ratio.parse::<BigUint>().unwrap(),
+                "tick {tick}"
+            );
+        }
+
+        // Ekubo's MAX_SQRT_RATIO, up to the rounding of its tick math
+        let max_sqrt_ratio: BigUint = "6277100250585753475930931601400621808602321654880405518632"
+            .parse()
+            .unwrap();
+        let sqrt_ratio = sqrt_ratio_at_tick(MAX_TICK, &EKUBO_TICK_CONFIG);
+        assert!(max_sqrt_ratio.clone() - sqrt_ratio < max_sqrt_ratio / 10u64.pow(18));
+    }
+
+    #[test]
+    fn q64_96_sqrt_ratios_match_uniswap_tick_math() {
+        use crate::amm::jediswap_v2::pool::{MAX_TICK, MIN_TICK, UNISWAP_V3_TICK_CONFIG};
+
+        // TickMath.getSqrtRatioAtTick, which rounds up where the local math rounds down
+        let known = [
+            (0, "79228162514264337593543950336"),
+            (1, "79232123823359799118286999568"),
+            (-1, "79224201403219477170569942574"),
+            (MIN_TICK, "4295128739"),
+            (
+                MAX_TICK,
+                "1461446703485210103287273052203988822378723970342",
+            ),
+        ];
+        for (tick, sqrt_ratio) in known {
+            let expected: BigUint = sqrt_ratio.parse().unwrap();
+            let sqrt_ratio = sqrt_ratio_at_tick(tick, &UNISWAP_V3_TICK_CONFIG);
+            assert!(sqrt_ratio <= expected, "tick {tick}");
+            assert!(
+                &expected - sqrt_ratio <= BigUint::one().max(&expected / 10u64.pow(18)),
+                "tick {tick}"
+            );
+        }
+    }
+
+    #[test]
+    fn exact_input_swap_crosses_an_initialized_tick() {
+        use crate::amm::jediswap_v2::pool::UNISWAP_V3_TICK_CONFIG as CONFIG;
+
+        // Positions on [-100, 100] and [-200, 200]
+        let (inner, outer) = (E18, 2 * E18);
+        let tick_data = TickData {
+            ticks: BTreeMap::from([
+                (-200, outer as i128),
+                (-100, inner as i128),
+                (100, -(inner as i128)),
+                (200, -(outer as i128)),
+            ]),
+            lower: -300,
+            upper: 300,
+        };
+        let state = ConcentratedLiquidityState {
+            sqrt_ratio: sqrt_ratio_at_tick(0, &CONFIG),
+            tick: 0,
+            liquidity: inner + outer,
+        };
+        let (fee, fee_denominator) = (big(3_000), big(1_000_000));
+        let amount_in = big(2 * E18 / 100);
+
+        let result = simulate_exact_input(
+            &CONFIG,
+            &state,
+            &tick_data,
+            &fee,
+            &fee_denominator,
+            true,
+            &amount_in,
+        )
+        .unwrap();
+
+        // Down to tick -100 with both positions, then on with the outer one only
+        let tick_sqrt_ratio = sqrt_ratio_at_tick(-100, &CONFIG);
+        let (_, first_in, first_out) = compute_swap_step(
+            &state.sqrt_ratio,
+            &tick_sqrt_ratio,
+            inner + outer,
+            &amount_in,
+            &fee,
+            &fee_denominator,
+            CONFIG.resolution,
+        );
+        let (sqrt_ratio, second_in, second_out) = compute_swap_step(
+            &tick_sqrt_ratio,
+            &sqrt_ratio_at_tick(-200, &CONFIG),
+            outer,
+            &(&amount_in - &first_in),
+            &fee,
+            &fee_denominator,
+            CONFIG.resolution,
+        );
+
+        assert!(first_in < amount_in);
+        assert_eq!(result.amount_in, first_in + second_in);
+        assert_eq!(result.amount_out, first_out + second_out);
+        assert_eq!(result.state.sqrt_ratio, sqrt_ratio);
+        assert_eq!(result.state.liquidity, outer);
+        assert!((-200..-100).contains(&result.state.tick));
+    }
+
+    #[test]
+    fn next_sqrt_ratio_from_output_matches_uniswap() {
+        // SqrtPriceMath.getNextSqrtPriceFromOutput at price 1 with 1e18 liquidity and 0.1e18 out
+        let sqrt_ratio = BigUint::one() << 96;
+        let amount = big(E18 / 10);
+
+        assert_eq!(
+            next_sqrt_ratio_from_amount1_output(&sqrt_ratio, E18, &amount, 96),
+            "71305346262837903834189555302".parse().unwrap()
+        );
+        assert_eq!(
+            next_sqrt_ratio_from_amount0_output(&sqrt_ratio, E18, &amount, 96),
+            "88031291682515930659493278152".parse().unwrap()
+        );
+    }
+
+    #[test]
+    fn exact_output_step_matches_uniswap_swap_math() {
+        // SwapMath.computeSwapStep with a 0.06% fee, from price 1 towards price 1.01, then
+        // towards price 100
+        let sqrt_ratio = BigUint::one() << 96;
+        let (fee, fee_denominator) = (big(600), big(1_000_000));
+
+        let target: BigUint = "79623317895830914510639640423".parse().unwrap();
+        let (sqrt_ratio_next, amount_in, amount_out) = compute_swap_step_exact_output(
+            &sqrt_ratio,
+            &target,
+            2 * E18,
+            &big(E18),
+            &fee,
+            &fee_denominator,
+            96,
+        );
+        assert_eq!(sqrt_ratio_next, target);
+        assert_eq!(amount_in, big(9_975_124_224_178_055 + 5_988_667_735_148));
+        assert_eq!(amount_out, big(9_925_619_580_021_728));
+
+        let target = &sqrt_ratio * 10u32;
+        let (sqrt_ratio_next, amount_in, amount_out) = compute_swap_step_exact_output(
+            &sqrt_ratio,
+            &target,
+            2 * E18,
+            &big(E18),
+            &fee,
+            &fee_denominator,
+            96,
+        );
+        assert_eq!(
+            sqrt_ratio_next,
+            "158456325028528675187087900672".parse().unwrap()
+        );
+        assert_eq!(amount_in, big(2 * E18 + 1_200_720_432_259_356));
+        assert_eq!(amount_out, big(E18));
+    }
+
+    #[test]
+    fn exact_output_swap_crosses_an_initialized_tick() {
+        use crate::amm::jediswap_v2::pool::UNISWAP_V3_TICK_CONFIG as CONFIG;
+
+        let (inner, outer) = (E18, 2 * E18);
+        let tick_data = TickData {
+            ticks: BTreeMap::from([
+                (-200, outer as i128),
+                (-100, inner as i128),
+                (100, -(inner as i128)),
+                (200, -(outer as i128)),
+            ]),
+            lower: -300,
+            upper: 300,
+        };
+        let state = ConcentratedLiquidityState {
+            sqrt_ratio: sqrt_ratio_at_tick(0, &CONFIG),
+            tick: 0,
+            liquidity: inner + outer,
+        };
+        let (fee, fee_denominator) = (big(3_000), big(1_000_000));
+        let amount_in = big(2 * E18 / 100);
+
+        let exact_input = simulate_exact_input(
+            &CONFIG,
+            &state,
+            &tick_data,
+            &fee,
+            &fee_denominator,
+            true,
+            &amount_in,
+        )
+        .unwrap();
+        let exact_output = simulate_exact_output(
+            &CONFIG,
+            &state,
+            &tick_data,
+            &fee,
+            &fee_denominator,
+            true,
+            &exact_input.amount_out,
+        )
+        .unwrap();
+
+        // Rounded up, but never above the input that produced the output
+        assert_eq!(exact_output.amount_out, exact_input.amount_out);
+        assert!(exact_output.amount_in <= amount_in);
+        assert!(&amount_in - &exact_output.amount_in <= big(10));
+        assert_eq!(exact_output.state.liquidity, outer);
+
+        let requoted = simulate_exact_input(
+            &CONFIG,
+            &state,
+            &tick_data,
+            &fee,
+            &fee_denominator,
+            true,
+            &exact_output.amount_in,
+        )
+        .unwrap();
+        assert!(requoted.amount_out >= exact_input.amount_out);
+    }
+
+    #[test]
+    fn exact_input_swap_fails_past_the_last_tick() {
+        use crate::amm::jediswap_v2::pool::{MIN_TICK, UNISWAP_V3_TICK_CONFIG as CONFIG};
+
+        let tick_data = TickData {
+            ticks: BTreeMap::new(),
+            lower: MIN_TICK,
+            upper: 0,
+        };
+        let state = ConcentratedLiquidityState {
+            sqrt_ratio: sqrt_ratio_at_tick(0, &CONFIG),
+            tick: 0,
+            liquidity: E18,
+        };
+
+        // Pushing the price to the lowest tick takes about 2^64 token0, so this much cannot
+        // all be swapped
+        let result = simulate_exact_input(
+            &CONFIG,
+            &state,
+            &tick_data,
+            &big(3_000),
+            &big(1_000_000),
+            true,
+            &(big(E18) << 200u32),
+        );
+        assert!(matches!(
+            result,
+            Err(SwapSimulationError::InsufficientLiquidity)
+        ));
+    }
+
+    #[test]
+    fn exact_output_swap_fails_past_the_last_tick() {
+        use crate::amm::jediswap_v2::pool::{MIN_TICK, UNISWAP_V3_TICK_CONFIG as CONFIG};
+
+        let tick_data = TickData {
+            ticks: BTreeMap::new(),
+            lower: MIN_TICK,
+            upper: 0,
+        };
+        let state = ConcentratedLiquidityState {
+            sqrt_ratio: sqrt_ratio_at_tick(0, &CONFIG),
+            tick: 0,
+            liquidity: E18,
+        };
+
+        // All the token1 below the price is E18
+        let result = simulate_exact_output(
+            &CONFIG,
+            &state,
+            &tick_data,
+            &big(3_000),
+            &big(1_000_000),
+            true,
+            &big(E18),
+        );
+        assert!(matches!(
+            result,
+            Err(SwapSimulationError::InsufficientLiquidity)
+        ));
+    }
+
+    #[test]
+    fn solidly_stable_amount_out_matches_pair_outputs() {
+        // Pair.getAmountOut of Solidly stable pairs with a 0.02% fee: USDC/USDT at par, an
+        // unbalanced DAI/USDC pair, and a swap the size of the reserves
+        let e6 = 1_000_000;
+        let cases = [
+            (
+                1_000 * e6,
+                1_000_000 * e6,
+                1_000_000 * e6,
+                6,
+                6,
+                999_799_999,
+            ),
+            (
+                10_000 * E18,
+                1_200_000 * E18,
+                800_000 * e6,
+                18,
+                6,
+                9_827_234_366,
+            ),
+            (
+                1_000_000 * E18,
+                1_000_000 * E18,
+                1_000_000 * E18,
+                18,
+                18,
+                753_662_792_353_921_169_258_954,
+            ),
+        ];
+
+        for (amount_in, reserve_in, reserve_out, decimals_in, decimals_out, amount_out) in cases {
+            assert_eq!(
+                solidly_get_amount_out(
+                    &big(amount_in),
+                    &big(reserve_in),
+                    &big(reserve_out),
+                    decimals_in,
+                    decimals_out,
+                    Fee::Bps(2),
+                    true,
+                ),
+                big(amount_out)
+            );
+        }
+    }
+
+    #[test]
+    fn solidly_newton_solver_converges_on_the_invariant() {
+        let one = BigUint::from(SOLIDLY_PRECISION);
+        let reserve = big(1_000_000 * E18);
+        let xy = solidly_f(&reserve, &reserve, &one);
+
+        // Doubling the input reserve, starting from the old output reserve
+        let x0 = &reserve * 2u32;
+        let y = solidly_get_y(&x0, &xy, reserve.clone(), &one);
+
+        assert!(y < reserve);
+        assert!(solidly_f(&x0, &(&y - 2u32), &one) <= xy);
+        assert!(solidly_f(&x0, &(&y + 2u32), &one) >= xy);
+    }
+
+    #[test]
+    fn solidly_stable_amount_in_rounds_up() {
+        let e6 = 1_000_000;
+        let cases = [
+            (999_799_999, 1_000_000 * e6, 1_000_000 * e6, 6, 6),
+            (9_827_234_366, 1_200_000 * E18, 800_000 * e6, 18, 6),
+        ];
+
+        for (amount_out, reserve_in, reserve_out, decimals_in, decimals_out) in cases {
+            let (amount_out, reserve_in, reserve_out) =
+                (big(amount_out), big(reserve_in), big(reserve_out));
+            let quote = |amount_in: &BigUint| {
+                solidly_get_amount_out(
+                    amount_in,
+                    &reserve_in,
+                    &reserve_out,
+                    decimals_in,
+                    decimals_out,
+                    Fee::Bps(2),
+                    true,
+                )
+            };
+
+            let amount_in = solidly_get_amount_in(
+                &amount_out,
+                &reserve_in,
+                &reserve_out,
+                decimals_in,
+                decimals_out,
+                Fee::Bps(2),
+                true,
+            )
+            .unwrap();
+
+            // Enough for the output, and within a few units of the smallest input that is
+            assert!(quote(&amount_in) >= amount_out);
+            assert!(quote(&(amount_in - 4u32)) < amount_out);
+        }
+    }
+}
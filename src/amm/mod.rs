@@ -1,5 +1,20 @@
+pub mod analytics;
+pub mod batch;
+pub mod ekubo;
+pub mod erc4626;
 pub mod factory;
+pub mod fetch;
 pub mod jediswap;
+pub mod jediswap_v2;
+pub mod lookup;
+pub mod math;
+pub mod myswap;
+pub mod nostra;
 pub mod pool;
+pub mod route;
+pub mod sithswap;
+pub mod solidly;
 pub mod tenkswap;
 pub mod types;
+pub mod uniswap_v2;
+pub mod verify;
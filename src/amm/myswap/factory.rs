@@ -0,0 +1,146 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use starknet::{
+    core::types::{BlockId, Felt},
+    providers::Provider,
+};
+
+use super::{
+    get_data::{self, get_pool_count},
+    pool::MySwapPool,
+};
+use crate::{
+    amm::{batch::populate_amms, factory::AutomatedMarketMakerFactory, pool::AMM},
+    errors::AMMError,
+    utils::batch_call_contracts,
+};
+
+/// Enumerates the pools of the mySwap contract by pool id.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MySwapFactory {
+    pub contract_address: Felt,
+}
+
+#[async_trait]
+impl AutomatedMarketMakerFactory for MySwapFactory {
+    fn address(&self) -> Felt {
+        self.contract_address
+    }
+
+    async fn fetch_all_pools<P>(&mut self, provider: Arc<P>) -> Result<Vec<AMM>, AMMError>
+    where
+        P: Provider + Sync + Send,
+    {
+        let block_number = provider.block_number().await?;
+        let mut all_pools = self
+            .get_new_pools(0, block_number, provider.clone())
+            .await?;
+        self.populate_amm_data(&mut all_pools, BlockId::Number(block_number), provider)
+            .await?;
+
+        Ok(all_pools)
+    }
+
+    /// mySwap does not emit an event when a pool is created, see [`MySwapFactory::get_new_pools`].
+    fn amm_created_event_signature(&self) -> Vec<Vec<Felt>> {
+        vec![]
+    }
+
+    async fn populate_amm_data<P>(
+        &self,
+        amms: &mut [AMM],
+        block_id: BlockId,
+        provider: Arc<P>,
+    ) -> Result<(), AMMError>
+    where
+        P: Provider + Send + Sync,
+    {
+        populate_amms(amms, block_id, provider).await
+    }
+}
+
+impl MySwapFactory {
+    pub fn new(contract_address: Felt) -> MySwapFactory {
+        MySwapFactory { contract_address }
+    }
+
+    /// Returns an unsynced pool for every pool created in `[from_block, to_block]`. Pool ids
+    /// are sequential, so the new pools are the ids past the pool count before `from_block`,
+    /// which is zero when the contract was not deployed yet.
+    pub async fn get_new_pools<P>(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        provider: Arc<P>,
+    ) -> Result<Vec<AMM>, AMMError>
+    where
+        P: Provider + Send + Sync,
+    {
+        let known_count = match from_block.checked_sub(1) {
+            Some(block) => get_pool_count(
+                self.contract_address,
+                BlockId::Number(block),
+                provider.clone(),
+            )
+            .await
+            .unwrap_or(0),
+            None => 0,
+        };
+        let pool_count =
+            get_pool_count(self.contract_address, BlockId::Number(to_block), provider).await?;
+
+        Ok((known_count + 1..=pool_count)
+            .map(|pool_id| {
+                AMM::MySwapPool(MySwapPool {
+                    contract_address: self.contract_address,
+                    pool_id,
+                    ..Default::default()
+                })
+            })
+            .collect())
+    }
+
+    /// Returns the pools of `token_a`/`token_b`, synced at `block_id`. mySwap has no pair lookup,
+    /// so every pool is read, with batched requests.
+    pub async fn get_pools_for_pair<P>(
+        &self,
+        token_a: Felt,
+        token_b: Felt,
+        block_id: BlockId,
+        provider: Arc<P>,
+    ) -> Result<Vec<AMM>, AMMError>
+    where
+        P: Provider + Send + Sync,
+    {
+        let pool_count = get_pool_count(self.contract_address, block_id, provider.clone()).await?;
+
+        let mut calls = vec![];
+        for pool_id in 1..=pool_count {
+            calls.extend(get_data::metadata_calls(self.contract_address, pool_id)?);
+        }
+        let results = batch_call_contracts(provider.clone(), calls, block_id)
+            .await
+            .map_err(|_| AMMError::BatchRequestError)?;
+
+        let mut pools = vec![];
+        for (pool_id, result) in (1..=pool_count).zip(results.chunks(1)) {
+            let mut pool = MySwapPool {
+                contract_address: self.contract_address,
+                pool_id,
+                ..Default::default()
+            };
+            get_data::set_metadata(&mut pool, result).ok_or(AMMError::PoolDataError)?;
+            if (pool.token_a, pool.token_b) == (token_a, token_b)
+                || (pool.token_a, pool.token_b) == (token_b, token_a)
+            {
+                pools.push(AMM::MySwapPool(pool));
+            }
+        }
+
+        populate_amms(&mut pools, block_id, provider).await?;
+
+        Ok(pools)
+    }
+}
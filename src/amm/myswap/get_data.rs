@@ -0,0 +1,170 @@
+use std::sync::Arc;
+
+use starknet::{
+    core::types::{BlockId, Felt, FunctionCall},
+    providers::Provider,
+};
+
+use super::pool::MySwapPool;
+use crate::{
+    amm::types::{Amount, Fee, Reserves},
+    errors::AMMError,
+    utils::{call_contract, felt_to_u128, felt_to_u8, function_call},
+};
+
+/// Decoded mySwap `Pool { name, token_a_address, token_a_reserves, token_b_address,
+/// token_b_reserves, fee_percentage, cfmm_type, liq_token }` struct.
+struct PoolData {
+    token_a: Felt,
+    token_b: Felt,
+    reserve_a: Amount,
+    reserve_b: Amount,
+    fee: Fee,
+}
+
+async fn get_pool<P>(
+    contract_address: Felt,
+    pool_id: u64,
+    block_id: BlockId,
+    provider: Arc<P>,
+) -> Result<PoolData, AMMError>
+where
+    P: Provider + Send + Sync,
+{
+    let result = call_contract(
+        provider,
+        contract_address,
+        "get_pool",
+        vec![Felt::from(pool_id)],
+        block_id,
+    )
+    .await
+    .map_err(|_| AMMError::PoolDataError)?;
+
+    decode_pool(&result).ok_or(AMMError::PoolDataError)
+}
+
+fn decode_pool(result: &[Felt]) -> Option<PoolData> {
+    if result.len() < 8 {
+        return None;
+    }
+
+    Some(PoolData {
+        token_a: result[1],
+        reserve_a: Amount::from_u256(result[2], result[3]).ok()?,
+        token_b: result[4],
+        reserve_b: Amount::from_u256(result[5], result[6]).ok()?,
+        fee: Fee::Bps(felt_to_u128(result[7]) as u32),
+    })
+}
+
+/// Calls returning the tokens, reserves and fee of a pool, to populate many pools with
+/// batched requests.
+pub fn metadata_calls(contract_address: Felt, pool_id: u64) -> Result<Vec<FunctionCall>, AMMError> {
+    reserve_calls(contract_address, pool_id)
+}
+
+/// Writes the results of [`metadata_calls`] into `pool`.
+pub fn set_metadata(pool: &mut MySwapPool, results: &[Vec<Felt>]) -> Option<()> {
+    let data = decode_pool(results.first()?)?;
+
+    pool.token_a = data.token_a;
+    pool.token_b = data.token_b;
+    pool.reserve_a = data.reserve_a;
+    pool.reserve_b = data.reserve_b;
+    pool.fee = data.fee;
+
+    Some(())
+}
+
+/// Calls returning the reserves of a pool, decoded by [`decode_reserves`].
+pub fn reserve_calls(contract_address: Felt, pool_id: u64) -> Result<Vec<FunctionCall>, AMMError> {
+    let call = function_call(contract_address, "get_pool", vec![Felt::from(pool_id)])
+        .map_err(|_| AMMError::PoolDataError)?;
+
+    Ok(vec![call])
+}
+
+pub fn decode_reserves(results: &[Vec<Felt>]) -> Option<Reserves> {
+    let data = decode_pool(results.first()?)?;
+
+    Some(Reserves {
+        reserve_a: data.reserve_a,
+        reserve_b: data.reserve_b,
+    })
+}
+
+pub async fn get_pool_info<P>(
+    contract_address: Felt,
+    pool_id: u64,
+    block_id: BlockId,
+    provider: Arc<P>,
+) -> Result<MySwapPool, AMMError>
+where
+    P: Provider + Send + Sync,
+{
+    let pool = get_pool(contract_address, pool_id, block_id, provider.clone()).await?;
+
+    let token_a_decimals =
+        call_contract(provider.clone(), pool.token_a, "decimals", vec![], block_id)
+            .await
+            .map_err(|_| AMMError::PoolDataError)?[0];
+
+    let token_b_decimals =
+        call_contract(provider.clone(), pool.token_b, "decimals", vec![], block_id)
+            .await
+            .map_err(|_| AMMError::PoolDataError)?[0];
+
+    Ok(MySwapPool::new(
+        contract_address,
+        pool_id,
+        pool.token_a,
+        pool.token_b,
+        felt_to_u8(token_a_decimals),
+        felt_to_u8(token_b_decimals),
+        pool.reserve_a,
+        pool.reserve_b,
+        pool.fee,
+    ))
+}
+
+pub async fn get_reserves<P>(
+    contract_address: Felt,
+    pool_id: u64,
+    block_id: BlockId,
+    provider: Arc<P>,
+) -> Result<Reserves, AMMError>
+where
+    P: Provider + Send + Sync,
+{
+    let pool = get_pool(contract_address, pool_id, block_id, provider)
+        .await
+        .map_err(|_| AMMError::SyncError(contract_address))?;
+
+    Ok(Reserves {
+        reserve_a: pool.reserve_a,
+        reserve_b: pool.reserve_b,
+    })
+}
+
+/// Returns the number of pools in the mySwap contract. Pool ids start at 1.
+pub async fn get_pool_count<P>(
+    contract_address: Felt,
+    block_id: BlockId,
+    provider: Arc<P>,
+) -> Result<u64, AMMError>
+where
+    P: Provider + Send + Sync,
+{
+    let count = call_contract(
+        provider,
+        contract_address,
+        "get_total_number_of_pools",
+        vec![],
+        block_id,
+    )
+    .await
+    .map_err(|_| AMMError::PoolDataError)?[0];
+
+    Ok(felt_to_u128(count) as u64)
+}
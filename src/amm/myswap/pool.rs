@@ -0,0 +1,249 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use num_traits::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use starknet::{
+    core::types::{BlockId, BlockTag, Felt, StarknetError},
+    providers::Provider,
+};
+use tracing::instrument;
+
+use super::get_data::{get_pool_info, get_reserves};
+use crate::{
+    amm::{
+        math::{
+            constant_product_amount_in, constant_product_amount_out, ratio_to_f64,
+            reserves_price_decimal,
+        },
+        pool::AutomatedMarketMaker,
+        types::{Amount, Fee, PoolId, Reserves, Token},
+    },
+    errors::{AMMError, ArithmeticError, SwapSimulationError},
+};
+
+/// A constant product pool stored inside the single mySwap contract under `pool_id`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct MySwapPool {
+    pub contract_address: Felt,
+    pub pool_id: u64,
+    pub token_a: Felt,
+    pub token_b: Felt,
+    pub token_a_decimals: u8,
+    pub token_b_decimals: u8,
+    pub reserve_a: Amount,
+    pub reserve_b: Amount,
+    pub fee: Fee,
+}
+
+#[async_trait]
+impl AutomatedMarketMaker for MySwapPool {
+    fn address(&self) -> Felt {
+        self.contract_address
+    }
+
+    fn id(&self) -> PoolId {
+        PoolId::new(self.contract_address, vec![Felt::from(self.pool_id)])
+    }
+
+    fn token_handles(&self) -> Vec<Token> {
+        vec![
+            Token::new(self.token_a, self.token_a_decimals),
+            Token::new(self.token_b, self.token_b_decimals),
+        ]
+    }
+
+    fn reserves(&self) -> Vec<Amount> {
+        vec![self.reserve_a, self.reserve_b]
+    }
+
+    #[instrument(skip(self, provider), level = "debug")]
+    async fn sync<P>(&mut self, block_id: BlockId, provider: Arc<P>) -> Result<(), StarknetError>
+    where
+        P: Provider + Send + Sync,
+    {
+        let Reserves {
+            reserve_a,
+            reserve_b,
+        } = get_reserves(self.contract_address, self.pool_id, block_id, provider).await?;
+        tracing::info!(
+            ?reserve_a,
+            ?reserve_b,
+            pool_id = self.pool_id,
+            "mySwap sync"
+        );
+
+        self.reserve_a = reserve_a;
+        self.reserve_b = reserve_b;
+
+        Ok(())
+    }
+
+    fn calculate_price(&self, base_token: Felt, quote_token: Felt) -> Result<f64, AMMError> {
+        self.validate_swap_tokens(base_token, quote_token)?;
+
+        let reserve_a = self.reserve_a.to_biguint().to_f64().unwrap_or_default()
+            / 10f64.powi(self.token_a_decimals as i32);
+        let reserve_b = self.reserve_b.to_biguint().to_f64().unwrap_or_default()
+            / 10f64.powi(self.token_b_decimals as i32);
+
+        let price = if base_token == self.token_a {
+            ratio_to_f64(reserve_b, reserve_a)
+        } else {
+            ratio_to_f64(reserve_a, reserve_b)
+        };
+
+        Ok(price?)
+    }
+
+    fn calculate_price_decimal(
+        &self,
+        base_token: Felt,
+        quote_token: Felt,
+    ) -> Result<Decimal, AMMError> {
+        self.validate_swap_tokens(base_token, quote_token)?;
+
+        let reserve_a = self.reserve_a.to_biguint();
+        let reserve_b = self.reserve_b.to_biguint();
+
+        let price = if base_token == self.token_a {
+            reserves_price_decimal(
+                &reserve_a,
+                &reserve_b,
+                self.token_a_decimals,
+                self.token_b_decimals,
+            )
+        } else {
+            reserves_price_decimal(
+                &reserve_b,
+                &reserve_a,
+                self.token_b_decimals,
+                self.token_a_decimals,
+            )
+        };
+
+        price.map_err(AMMError::from)
+    }
+
+    fn simulate_swap(
+        &self,
+        token_in: Felt,
+        token_out: Felt,
+        amount_in: Amount,
+    ) -> Result<Amount, SwapSimulationError> {
+        self.validate_swap_tokens(token_in, token_out)?;
+
+        if self.token_a == token_in {
+            Ok(self.get_amount_out(amount_in, self.reserve_a, self.reserve_b)?)
+        } else {
+            Ok(self.get_amount_out(amount_in, self.reserve_b, self.reserve_a)?)
+        }
+    }
+
+    /// Locally simulates a swap in the AMM.
+    /// Mutates the AMM state to the state of the AMM after swapping.
+    /// Returns the amount received for `amount_in` of `token_in`.
+    fn simulate_swap_mut(
+        &mut self,
+        token_in: Felt,
+        token_out: Felt,
+        amount_in: Amount,
+    ) -> Result<Amount, SwapSimulationError> {
+        let amount_out = self.simulate_swap(token_in, token_out, amount_in)?;
+
+        if self.token_a == token_in {
+            self.reserve_a = self.reserve_a.checked_add(amount_in)?;
+            self.reserve_b = self.reserve_b.checked_sub(amount_out)?;
+        } else {
+            self.reserve_b = self.reserve_b.checked_add(amount_in)?;
+            self.reserve_a = self.reserve_a.checked_sub(amount_out)?;
+        }
+
+        Ok(amount_out)
+    }
+
+    /// Returns the amount of `token_in` needed to receive exactly `amount_out` of
+    /// `token_out`, rounded up.
+    fn get_amount_in(
+        &self,
+        token_in: Felt,
+        token_out: Felt,
+        amount_out: Amount,
+    ) -> Result<Amount, SwapSimulationError> {
+        self.validate_swap_tokens(token_in, token_out)?;
+
+        let (reserve_in, reserve_out) = if self.token_a == token_in {
+            (self.reserve_a, self.reserve_b)
+        } else {
+            (self.reserve_b, self.reserve_a)
+        };
+
+        let amount_in = constant_product_amount_in(
+            &amount_out.to_biguint(),
+            &reserve_in.to_biguint(),
+            &reserve_out.to_biguint(),
+            self.fee,
+        )?;
+
+        Ok(Amount::from_biguint(&amount_in)?)
+    }
+}
+
+impl MySwapPool {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        contract_address: Felt,
+        pool_id: u64,
+        token_a: Felt,
+        token_b: Felt,
+        token_a_decimals: u8,
+        token_b_decimals: u8,
+        reserve_a: Amount,
+        reserve_b: Amount,
+        fee: Fee,
+    ) -> MySwapPool {
+        MySwapPool {
+            contract_address,
+            pool_id,
+            token_a,
+            token_b,
+            token_a_decimals,
+            token_b_decimals,
+            reserve_a,
+            reserve_b,
+            fee,
+        }
+    }
+
+    pub async fn new_from_pool_id<P>(
+        contract_address: Felt,
+        pool_id: u64,
+        provider: Arc<P>,
+    ) -> Result<Self, AMMError>
+    where
+        P: Provider + Send + Sync,
+    {
+        get_pool_info(
+            contract_address,
+            pool_id,
+            BlockId::Tag(BlockTag::Latest),
+            provider,
+        )
+        .await
+    }
+
+    pub fn get_amount_out(
+        &self,
+        amount_in: Amount,
+        reserve_in: Amount,
+        reserve_out: Amount,
+    ) -> Result<Amount, ArithmeticError> {
+        Amount::from_biguint(&constant_product_amount_out(
+            &amount_in.to_biguint(),
+            &reserve_in.to_biguint(),
+            &reserve_out.to_biguint(),
+            self.fee,
+        ))
+    }
+}
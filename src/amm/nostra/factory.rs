@@ -0,0 +1,159 @@
+use std::{borrow::Cow, sync::Arc};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use starknet::{
+    core::types::{BlockId, EmittedEvent, Felt},
+    macros::selector,
+    providers::Provider,
+};
+
+use super::{get_data::get_pool_info, pool::NostraPool};
+use crate::{
+    amm::{
+        batch::populate_amms,
+        factory::AutomatedMarketMakerFactory,
+        fetch::{fetch_pools, FetchError, FetchOptions},
+        lookup::get_pair_address,
+        pool::AMM,
+        types::{Amount, Fee},
+        uniswap_v2::descriptor::PairEnumeration,
+    },
+    errors::{AMMError, EventLogError},
+};
+
+const PAIR_ENUMERATION: PairEnumeration = PairEnumeration::Array {
+    all_pairs: Cow::Borrowed("all_pairs"),
+};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NostraFactory {
+    pub factory_address: Felt,
+}
+
+#[async_trait]
+impl AutomatedMarketMakerFactory for NostraFactory {
+    fn address(&self) -> Felt {
+        self.factory_address
+    }
+
+    async fn fetch_all_pools<P>(&mut self, provider: Arc<P>) -> Result<Vec<AMM>, AMMError>
+    where
+        P: Provider + Sync + Send,
+    {
+        self.fetch_pools(&FetchOptions::default(), provider)
+            .await
+            .map_err(|err| err.source)
+    }
+
+    fn amm_created_event_signature(&self) -> Vec<Vec<Felt>> {
+        vec![vec![selector!("PairCreated")]]
+    }
+
+    async fn populate_amm_data<P>(
+        &self,
+        amms: &mut [AMM],
+        block_id: BlockId,
+        provider: Arc<P>,
+    ) -> Result<(), AMMError>
+    where
+        P: Provider + Send + Sync,
+    {
+        populate_amms(amms, block_id, provider).await
+    }
+}
+
+impl NostraFactory {
+    pub fn new(factory_address: Felt) -> NostraFactory {
+        NostraFactory { factory_address }
+    }
+
+    /// Fetches and populates every pair of the factory, see [`fetch_pools`].
+    pub async fn fetch_pools<P>(
+        &self,
+        options: &FetchOptions,
+        provider: Arc<P>,
+    ) -> Result<Vec<AMM>, FetchError>
+    where
+        P: Provider + Sync + Send,
+    {
+        fetch_pools(
+            self,
+            &PAIR_ENUMERATION,
+            |pool_address| {
+                // The curve and fee are read when the pool is populated
+                AMM::NostraPool(NostraPool::new(
+                    pool_address,
+                    Felt::ZERO,
+                    Felt::ZERO,
+                    0,
+                    0,
+                    Amount::ZERO,
+                    Amount::ZERO,
+                    Fee::default(),
+                    false,
+                ))
+            },
+            options,
+            provider,
+        )
+        .await
+    }
+
+    /// Returns the pair of `token_a`/`token_b`, synced at `block_id`, if it exists.
+    pub async fn get_pools_for_pair<P>(
+        &self,
+        token_a: Felt,
+        token_b: Felt,
+        block_id: BlockId,
+        provider: Arc<P>,
+    ) -> Result<Vec<AMM>, AMMError>
+    where
+        P: Provider + Send + Sync,
+    {
+        match get_pair_address(
+            self.factory_address,
+            "get_pair",
+            vec![token_a, token_b],
+            block_id,
+            provider.clone(),
+        )
+        .await?
+        {
+            Some(pool_address) => Ok(vec![AMM::NostraPool(
+                get_pool_info(pool_address, block_id, provider).await?,
+            )]),
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Builds an unsynced pool from a `PairCreated { token_0, token_1, pair, total_pairs }`
+    /// event. The curve and fee are only known once the pool is populated.
+    pub fn new_empty_amm_from_log(&self, log: EmittedEvent) -> Result<AMM, AMMError> {
+        if log.keys.first() != Some(&selector!("PairCreated")) {
+            return Err(EventLogError::InvalidEventSignature.into());
+        }
+
+        // Members may be emitted as keys or as data depending on how they are annotated
+        let fields: Vec<Felt> = log.keys[1..]
+            .iter()
+            .chain(log.data.iter())
+            .cloned()
+            .collect();
+        if fields.len() < 3 {
+            return Err(AMMError::UnrecognizedPoolCreatedEventLog);
+        }
+
+        Ok(AMM::NostraPool(NostraPool::new(
+            fields[2],
+            fields[0],
+            fields[1],
+            0,
+            0,
+            Amount::ZERO,
+            Amount::ZERO,
+            Fee::default(),
+            false,
+        )))
+    }
+}
@@ -0,0 +1,118 @@
+use std::sync::Arc;
+
+use starknet::{
+    core::types::{BlockId, Felt, FunctionCall},
+    providers::Provider,
+};
+
+use super::pool::{Nostra, NostraPool};
+use crate::{
+    amm::{
+        solidly::{get_data::get_reserves, pool::SolidlyFork},
+        types::{Fee, Reserves},
+    },
+    errors::AMMError,
+    utils::{call_contract, felt_to_u128, felt_to_u8, function_call},
+};
+
+pub async fn get_pool_info<P>(
+    pool_address: Felt,
+    block_id: BlockId,
+    provider: Arc<P>,
+) -> Result<NostraPool, AMMError>
+where
+    P: Provider + Send + Sync,
+{
+    let token_0_address = call_contract(provider.clone(), pool_address, "token0", vec![], block_id)
+        .await
+        .map_err(|_| AMMError::PoolDataError)?[0];
+
+    let token_1_address = call_contract(provider.clone(), pool_address, "token1", vec![], block_id)
+        .await
+        .map_err(|_| AMMError::PoolDataError)?[0];
+
+    let token0_decimals = call_contract(
+        provider.clone(),
+        token_0_address,
+        "decimals",
+        vec![],
+        block_id,
+    )
+    .await
+    .map_err(|_| AMMError::PoolDataError)?[0];
+
+    let token1_decimals = call_contract(
+        provider.clone(),
+        token_1_address,
+        "decimals",
+        vec![],
+        block_id,
+    )
+    .await
+    .map_err(|_| AMMError::PoolDataError)?[0];
+
+    let stable = call_contract(
+        provider.clone(),
+        pool_address,
+        "is_stable",
+        vec![],
+        block_id,
+    )
+    .await
+    .map_err(|_| AMMError::PoolDataError)?[0];
+
+    let fee = call_contract(
+        provider.clone(),
+        pool_address,
+        "get_swap_fee",
+        vec![],
+        block_id,
+    )
+    .await
+    .map_err(|_| AMMError::PoolDataError)?[0];
+
+    let Reserves {
+        reserve_a,
+        reserve_b,
+    } = get_reserves(pool_address, Nostra::RESERVES_SELECTOR, block_id, provider).await?;
+
+    Ok(NostraPool::new(
+        pool_address,
+        token_0_address,
+        token_1_address,
+        felt_to_u8(token0_decimals),
+        felt_to_u8(token1_decimals),
+        reserve_a,
+        reserve_b,
+        Fee::Bps(felt_to_u128(fee) as u32),
+        stable != Felt::ZERO,
+    ))
+}
+
+/// Calls returning the tokens of a pool, followed by its curve and fee, to populate many pools
+/// with batched requests.
+pub fn metadata_calls(pool_address: Felt) -> Result<Vec<FunctionCall>, AMMError> {
+    ["token0", "token1", "is_stable", "get_swap_fee"]
+        .iter()
+        .map(|method| function_call(pool_address, method, vec![]))
+        .collect::<Result<_, _>>()
+        .map_err(|_| AMMError::PoolDataError)
+}
+
+/// Writes the results of [`metadata_calls`] into `pool`.
+pub fn set_metadata(pool: &mut NostraPool, results: &[Vec<Felt>]) -> Option<()> {
+    let metadata = results
+        .iter()
+        .map(|result| result.first().copied())
+        .collect::<Option<Vec<Felt>>>()?;
+    let [token_a, token_b, stable, fee, ..] = metadata[..] else {
+        return None;
+    };
+
+    pool.token_a = token_a;
+    pool.token_b = token_b;
+    pool.stable = stable != Felt::ZERO;
+    pool.fee = Fee::Bps(felt_to_u128(fee) as u32);
+
+    Some(())
+}
@@ -0,0 +1,3 @@
+pub mod factory;
+pub mod get_data;
+pub mod pool;
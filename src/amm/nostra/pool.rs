@@ -0,0 +1,33 @@
+use std::sync::Arc;
+
+use starknet::{
+    core::types::{BlockId, BlockTag, Felt},
+    providers::Provider,
+};
+
+use super::get_data::get_pool_info;
+use crate::{
+    amm::solidly::pool::{SolidlyFork, SolidlyPool},
+    errors::AMMError,
+};
+
+/// Nostra, whose pairs expose their fee with `get_swap_fee`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Nostra;
+
+impl SolidlyFork for Nostra {
+    const NAME: &'static str = "Nostra";
+    const RESERVES_SELECTOR: &'static str = "get_reserves";
+}
+
+/// A Nostra pair.
+pub type NostraPool = SolidlyPool<Nostra>;
+
+impl NostraPool {
+    pub async fn new_from_address<P>(pool_address: Felt, provider: Arc<P>) -> Result<Self, AMMError>
+    where
+        P: Provider + Send + Sync,
+    {
+        get_pool_info(pool_address, BlockId::Tag(BlockTag::Latest), provider).await
+    }
+}
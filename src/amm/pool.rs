@@ -1,51 +1,130 @@
-use std::sync::Arc;
+use std::{
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
 
 use async_trait::async_trait;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use starknet::{
-    core::types::{Felt, StarknetError},
+    core::types::{BlockId, Felt, StarknetError},
     providers::Provider,
 };
 
-use super::jediswap::pool::JediswapPool;
-use crate::amm::tenkswap::pool::TenkSwapPool;
+use super::{
+    ekubo::pool::EkuboPool,
+    erc4626::pool::ERC4626Vault,
+    jediswap_v2::pool::JediswapV2Pool,
+    myswap::pool::MySwapPool,
+    nostra::pool::NostraPool,
+    sithswap::pool::SithSwapPool,
+    types::{Amount, PoolId, Token},
+    uniswap_v2::{legacy::LegacyV2Pool, pool::UniswapV2Pool},
+};
+use crate::errors::{AMMError, SwapSimulationError};
 
 #[async_trait]
 pub trait AutomatedMarketMaker {
     /// Returns the address of the AMM.
     fn address(&self) -> Felt;
 
-    /// Returns a vector of tokens in the AMM.
-    fn tokens(&self) -> Vec<Felt>;
+    /// Returns the identity of the pool.
+    ///
+    /// AMMs that keep several pools in one contract must override this, since `address`
+    /// alone does not tell their pools apart.
+    fn id(&self) -> PoolId {
+        PoolId::new(self.address(), vec![])
+    }
+
+    /// Returns the tokens of the AMM with their decimals.
+    fn token_handles(&self) -> Vec<Token>;
+
+    /// Returns a vector of tokens in the AMM, in the order of
+    /// [`token_handles`](Self::token_handles).
+    fn tokens(&self) -> Vec<Felt> {
+        self.token_handles()
+            .iter()
+            .map(|token| token.address)
+            .collect()
+    }
+
+    /// Returns the reserves of the AMM, in the order of [`tokens`](Self::tokens).
+    ///
+    /// Concentrated liquidity pools return the virtual reserves of their active liquidity.
+    fn reserves(&self) -> Vec<Amount>;
 
-    async fn sync<P>(&mut self, provider: Arc<P>) -> Result<(), StarknetError>
+    /// Syncs the AMM state to `block_id`, so that pools synced at the same block are
+    /// consistent with each other.
+    async fn sync<P>(&mut self, block_id: BlockId, provider: Arc<P>) -> Result<(), StarknetError>
     where
         P: Provider + Send + Sync;
 
-    /// Calculates a f64 representation of base token price in the AMM.
-    fn calculate_price(&self, base_token: Felt, quote_token: Felt) -> Result<f64, StarknetError>;
+    /// Calculates a f64 representation of base token price in the AMM. Fails like
+    /// [`calculate_price_decimal`](Self::calculate_price_decimal) when the price divides by an
+    /// empty reserve.
+    fn calculate_price(&self, base_token: Felt, quote_token: Felt) -> Result<f64, AMMError>;
+
+    /// Calculates the base token price in the AMM as a `Decimal`, computed from the raw
+    /// integer state so no precision is lost to `f64`.
+    fn calculate_price_decimal(
+        &self,
+        base_token: Felt,
+        quote_token: Felt,
+    ) -> Result<Decimal, AMMError>;
+
+    /// Checks that `token_in` and `token_out` are two different tokens of the AMM. Prices are
+    /// checked the same way, with the base token as `token_in`.
+    fn validate_swap_tokens(
+        &self,
+        token_in: Felt,
+        token_out: Felt,
+    ) -> Result<(), SwapSimulationError> {
+        let tokens = self.tokens();
+        if !tokens.contains(&token_in) {
+            return Err(SwapSimulationError::TokenInDoesNotExist(token_in));
+        }
+        if !tokens.contains(&token_out) {
+            return Err(SwapSimulationError::TokenOutDoesNotExist(token_out));
+        }
+        if token_in == token_out {
+            return Err(SwapSimulationError::IdenticalTokens);
+        }
+
+        Ok(())
+    }
 
     /// Locally simulates a swap in the AMM.
     ///
-    /// Returns the amount received for `amount_in` of `token_in`.
-    async fn simulate_swap<P>(
+    /// Returns the amount of `token_out` received for `amount_in` of `token_in`. Both tokens
+    /// must belong to the AMM, which may hold more than two.
+    fn simulate_swap(
         &self,
-        base_token: Felt,
-        amount_in: Felt,
-        provider: Arc<P>,
-    ) -> Result<Felt, StarknetError>
-    where
-        P: Provider + Send + Sync;
+        token_in: Felt,
+        token_out: Felt,
+        amount_in: Amount,
+    ) -> Result<Amount, SwapSimulationError>;
 
     /// Locally simulates a swap in the AMM.
     /// Mutates the AMM state to the state of the AMM after swapping.
-    /// Returns the amount received for `amount_in` of `token_in`.
+    /// Returns the amount of `token_out` received for `amount_in` of `token_in`.
     fn simulate_swap_mut(
         &mut self,
-        base_token: Felt,
-        quote_token: Felt,
-        amount_in: Felt,
-    ) -> Result<Felt, StarknetError>;
+        token_in: Felt,
+        token_out: Felt,
+        amount_in: Amount,
+    ) -> Result<Amount, SwapSimulationError>;
+
+    /// Locally simulates an exact output swap in the AMM.
+    ///
+    /// Returns the amount of `token_in` needed to receive exactly `amount_out` of
+    /// `token_out`, rounded up so that swapping it yields at least `amount_out`. Fails when
+    /// `amount_out` is at or above what the pool can provide.
+    fn get_amount_in(
+        &self,
+        token_in: Felt,
+        token_out: Felt,
+        amount_out: Amount,
+    ) -> Result<Amount, SwapSimulationError>;
 
     // async fn populate_data<P>(&mut self, middleware: Arc<P>) -> Result<(), StarknetError>
     // where
@@ -53,12 +132,32 @@ pub trait AutomatedMarketMaker {
 }
 
 macro_rules! amm {
-    ($($pool_type:ident),+ $(,)?) => {
+    (
+        $($pool_type:ident),+ $(,)?;
+        $($legacy_type:ident($legacy_data:ty) => $convert:path),* $(,)?
+    ) => {
         #[derive(Debug, Clone, Serialize, Deserialize)]
+        #[serde(from = "AMMRepr")]
         pub enum AMM {
             $($pool_type($pool_type),)+
         }
 
+        /// Serialized form of [`AMM`], which also reads pools of older checkpoints.
+        #[derive(Deserialize)]
+        enum AMMRepr {
+            $($pool_type($pool_type),)+
+            $($legacy_type($legacy_data),)*
+        }
+
+        impl From<AMMRepr> for AMM {
+            fn from(repr: AMMRepr) -> AMM {
+                match repr {
+                    $(AMMRepr::$pool_type(pool) => AMM::$pool_type(pool),)+
+                    $(AMMRepr::$legacy_type(pool) => $convert(pool),)*
+                }
+            }
+        }
+
         #[async_trait]
         impl AutomatedMarketMaker for AMM {
             fn address(&self) -> Felt{
@@ -67,41 +166,71 @@ macro_rules! amm {
                 }
             }
 
+            fn id(&self) -> PoolId {
+                match self {
+                    $(AMM::$pool_type(pool) => pool.id(),)+
+                }
+            }
+
+            fn token_handles(&self) -> Vec<Token> {
+                match self {
+                    $(AMM::$pool_type(pool) => pool.token_handles(),)+
+                }
+            }
+
             fn tokens(&self) -> Vec<Felt> {
                 match self {
                     $(AMM::$pool_type(pool) => pool.tokens(),)+
                 }
             }
 
-            async fn sync<P>(&mut self, middleware: Arc<P>) -> Result<(), StarknetError>
+            fn reserves(&self) -> Vec<Amount> {
+                match self {
+                    $(AMM::$pool_type(pool) => pool.reserves(),)+
+                }
+            }
+
+            async fn sync<P>(&mut self, block_id: BlockId, middleware: Arc<P>) -> Result<(), StarknetError>
             where
                 P: Provider + Send + Sync,
             {
                 match self {
-                    $(AMM::$pool_type(pool) => pool.sync(middleware).await,)+
+                    $(AMM::$pool_type(pool) => pool.sync(block_id, middleware).await,)+
                 }
             }
 
 
-            async fn simulate_swap<P>(&self, base_token: Felt, amount_in: Felt, provider: Arc<P>) -> Result<Felt, StarknetError> where P: Provider + Send + Sync {
+            fn simulate_swap(&self, token_in: Felt, token_out: Felt, amount_in: Amount) -> Result<Amount, SwapSimulationError> {
                 match self {
-                    $(AMM::$pool_type(pool) => pool.simulate_swap(base_token, amount_in, provider).await,)+
+                    $(AMM::$pool_type(pool) => pool.simulate_swap(token_in, token_out, amount_in),)+
                 }
             }
 
-            fn simulate_swap_mut(&mut self, base_token: Felt, quote_token: Felt, amount_in: Felt) -> Result<Felt, StarknetError> {
+            fn simulate_swap_mut(&mut self, token_in: Felt, token_out: Felt, amount_in: Amount) -> Result<Amount, SwapSimulationError> {
                 match self {
-                    $(AMM::$pool_type(pool) => pool.simulate_swap_mut(base_token, quote_token, amount_in),)+
+                    $(AMM::$pool_type(pool) => pool.simulate_swap_mut(token_in, token_out, amount_in),)+
+                }
+            }
+
+            fn get_amount_in(&self, token_in: Felt, token_out: Felt, amount_out: Amount) -> Result<Amount, SwapSimulationError> {
+                match self {
+                    $(AMM::$pool_type(pool) => pool.get_amount_in(token_in, token_out, amount_out),)+
                 }
             }
 
 
-            fn calculate_price(&self, base_token: Felt, quote_token: Felt) -> Result<f64, StarknetError> {
+            fn calculate_price(&self, base_token: Felt, quote_token: Felt) -> Result<f64, AMMError> {
                 match self {
                     $(AMM::$pool_type(pool) => pool.calculate_price(base_token, quote_token),)+
                 }
             }
 
+            fn calculate_price_decimal(&self, base_token: Felt, quote_token: Felt) -> Result<Decimal, AMMError> {
+                match self {
+                    $(AMM::$pool_type(pool) => pool.calculate_price_decimal(base_token, quote_token),)+
+                }
+            }
+
 
             // async fn populate_data<P>(&mut self, middleware: Arc<P>) -> Result<(), StarknetError>
             // where
@@ -116,12 +245,28 @@ macro_rules! amm {
 
         impl PartialEq for AMM {
             fn eq(&self, other: &Self) -> bool {
-                self.address() == other.address()
+                self.id() == other.id()
             }
         }
 
         impl Eq for AMM {}
+
+        impl Hash for AMM {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.id().hash(state);
+            }
+        }
     };
 }
 
-amm!(JediswapPool, TenkSwapPool);
+amm!(
+    EkuboPool,
+    JediswapV2Pool,
+    MySwapPool,
+    SithSwapPool,
+    NostraPool,
+    ERC4626Vault,
+    UniswapV2Pool;
+    JediswapPool(LegacyV2Pool) => LegacyV2Pool::into_jediswap,
+    TenkSwapPool(LegacyV2Pool) => LegacyV2Pool::into_tenkswap,
+);
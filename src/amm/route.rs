@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+use starknet::core::types::Felt;
+
+use super::{
+    pool::{AutomatedMarketMaker, AMM},
+    types::{Amount, PoolId},
+};
+use crate::errors::SwapSimulationError;
+
+/// A single swap of a route.
+#[derive(Debug, Clone)]
+pub struct Hop<'a> {
+    pub amm: &'a AMM,
+    pub token_in: Felt,
+    pub token_out: Felt,
+}
+
+impl<'a> Hop<'a> {
+    pub fn new(amm: &'a AMM, token_in: Felt, token_out: Felt) -> Hop<'a> {
+        Hop {
+            amm,
+            token_in,
+            token_out,
+        }
+    }
+}
+
+/// Amounts swapped by one hop of a simulated route.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HopAmounts {
+    pub pool: PoolId,
+    pub token_in: Felt,
+    pub token_out: Felt,
+    pub amount_in: Amount,
+    pub amount_out: Amount,
+}
+
+/// Simulates `route` on clones of its pools, feeding each hop with the output of the
+/// previous one, and returns the amounts of every hop.
+///
+/// A pool used by several hops keeps the state left by the earlier ones, so later hops see
+/// their price impact. The pools passed in are left untouched. Fails with
+/// [`SwapSimulationError::BrokenRoute`] when a hop does not swap the token received from the
+/// previous one.
+pub fn simulate_route(
+    route: &[Hop],
+    amount_in: Amount,
+) -> Result<Vec<HopAmounts>, SwapSimulationError> {
+    if let Some(idx) = route
+        .windows(2)
+        .position(|hops| hops[0].token_out != hops[1].token_in)
+    {
+        return Err(SwapSimulationError::BrokenRoute(idx + 1));
+    }
+
+    let mut pools: HashMap<PoolId, AMM> = HashMap::new();
+    let mut hops = Vec::with_capacity(route.len());
+    let mut amount = amount_in;
+
+    for hop in route {
+        let id = hop.amm.id();
+        let pool = pools.entry(id.clone()).or_insert_with(|| hop.amm.clone());
+
+        let amount_out = pool.simulate_swap_mut(hop.token_in, hop.token_out, amount)?;
+        hops.push(HopAmounts {
+            pool: id,
+            token_in: hop.token_in,
+            token_out: hop.token_out,
+            amount_in: amount,
+            amount_out,
+        });
+
+        amount = amount_out;
+    }
+
+    Ok(hops)
+}
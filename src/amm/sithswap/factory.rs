@@ -0,0 +1,195 @@
+use std::{borrow::Cow, sync::Arc};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use starknet::{
+    core::types::{BlockId, EmittedEvent, Felt},
+    macros::selector,
+    providers::Provider,
+};
+
+use super::{get_data::get_pool_info, pool::SithSwapPool};
+use crate::{
+    amm::{
+        batch::populate_amms,
+        factory::AutomatedMarketMakerFactory,
+        fetch::{fetch_pools, FetchError, FetchOptions},
+        lookup::get_pair_address,
+        pool::AMM,
+        types::{Amount, Fee},
+        uniswap_v2::descriptor::PairEnumeration,
+    },
+    errors::{AMMError, EventLogError},
+};
+
+/// Default fee of stable pairs.
+pub const STABLE_FEE: Fee = Fee::Bps(2);
+
+/// Default fee of volatile pairs.
+pub const VOLATILE_FEE: Fee = Fee::Bps(20);
+
+const PAIR_ENUMERATION: PairEnumeration = PairEnumeration::Indexed {
+    all_pairs_length: Cow::Borrowed("allPairsLength"),
+    all_pairs: Cow::Borrowed("allPairs"),
+};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SithSwapFactory {
+    pub factory_address: Felt,
+    pub stable_fee: Fee,
+    pub volatile_fee: Fee,
+}
+
+#[async_trait]
+impl AutomatedMarketMakerFactory for SithSwapFactory {
+    fn address(&self) -> Felt {
+        self.factory_address
+    }
+
+    async fn fetch_all_pools<P>(&mut self, provider: Arc<P>) -> Result<Vec<AMM>, AMMError>
+    where
+        P: Provider + Sync + Send,
+    {
+        self.fetch_pools(&FetchOptions::default(), provider)
+            .await
+            .map_err(|err| err.source)
+    }
+
+    fn amm_created_event_signature(&self) -> Vec<Vec<Felt>> {
+        vec![vec![selector!("PairCreated")]]
+    }
+
+    async fn populate_amm_data<P>(
+        &self,
+        amms: &mut [AMM],
+        block_id: BlockId,
+        provider: Arc<P>,
+    ) -> Result<(), AMMError>
+    where
+        P: Provider + Send + Sync,
+    {
+        populate_amms(amms, block_id, provider).await?;
+
+        for amm in amms.iter_mut() {
+            if let AMM::SithSwapPool(pool) = amm {
+                pool.fee = self.fee(pool.stable);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SithSwapFactory {
+    pub fn new(factory_address: Felt) -> SithSwapFactory {
+        SithSwapFactory {
+            factory_address,
+            stable_fee: STABLE_FEE,
+            volatile_fee: VOLATILE_FEE,
+        }
+    }
+
+    /// Returns the fee charged by pairs of the given curve.
+    pub fn fee(&self, stable: bool) -> Fee {
+        if stable {
+            self.stable_fee
+        } else {
+            self.volatile_fee
+        }
+    }
+
+    /// Fetches and populates every pair of the factory, see [`fetch_pools`].
+    pub async fn fetch_pools<P>(
+        &self,
+        options: &FetchOptions,
+        provider: Arc<P>,
+    ) -> Result<Vec<AMM>, FetchError>
+    where
+        P: Provider + Sync + Send,
+    {
+        fetch_pools(
+            self,
+            &PAIR_ENUMERATION,
+            |pool_address| {
+                // The curve is read, and the fee set, when the pool is populated
+                AMM::SithSwapPool(SithSwapPool::new(
+                    pool_address,
+                    Felt::ZERO,
+                    Felt::ZERO,
+                    0,
+                    0,
+                    Amount::ZERO,
+                    Amount::ZERO,
+                    self.volatile_fee,
+                    false,
+                ))
+            },
+            options,
+            provider,
+        )
+        .await
+    }
+
+    /// Returns the stable and volatile pairs of `token_a`/`token_b` that exist, synced at
+    /// `block_id`.
+    pub async fn get_pools_for_pair<P>(
+        &self,
+        token_a: Felt,
+        token_b: Felt,
+        block_id: BlockId,
+        provider: Arc<P>,
+    ) -> Result<Vec<AMM>, AMMError>
+    where
+        P: Provider + Send + Sync,
+    {
+        let mut amms = vec![];
+
+        for stable in [Felt::ZERO, Felt::ONE] {
+            if let Some(pool_address) = get_pair_address(
+                self.factory_address,
+                "getPair",
+                vec![token_a, token_b, stable],
+                block_id,
+                provider.clone(),
+            )
+            .await?
+            {
+                let mut pool = get_pool_info(pool_address, block_id, provider.clone()).await?;
+                pool.fee = self.fee(pool.stable);
+                amms.push(AMM::SithSwapPool(pool));
+            }
+        }
+
+        Ok(amms)
+    }
+
+    /// Builds an unsynced pool from a `PairCreated(token0, token1, stable, pair, all_pairs_length)`
+    /// event.
+    pub fn new_empty_amm_from_log(&self, log: EmittedEvent) -> Result<AMM, AMMError> {
+        if log.keys.first() != Some(&selector!("PairCreated")) {
+            return Err(EventLogError::InvalidEventSignature.into());
+        }
+
+        // Members may be emitted as keys or as data depending on how they are annotated
+        let fields: Vec<Felt> = log.keys[1..]
+            .iter()
+            .chain(log.data.iter())
+            .cloned()
+            .collect();
+        if fields.len() < 4 {
+            return Err(AMMError::UnrecognizedPoolCreatedEventLog);
+        }
+
+        let stable = fields[2] != Felt::ZERO;
+        Ok(AMM::SithSwapPool(SithSwapPool::new(
+            fields[3],
+            fields[0],
+            fields[1],
+            0,
+            0,
+            Amount::ZERO,
+            Amount::ZERO,
+            self.fee(stable),
+            stable,
+        )))
+    }
+}
@@ -0,0 +1,118 @@
+use std::sync::Arc;
+
+use starknet::{
+    core::types::{BlockId, Felt, FunctionCall},
+    providers::Provider,
+};
+
+use super::{
+    factory::{STABLE_FEE, VOLATILE_FEE},
+    pool::{SithSwap, SithSwapPool},
+};
+use crate::{
+    amm::{
+        solidly::{get_data::get_reserves, pool::SolidlyFork},
+        types::Reserves,
+    },
+    errors::AMMError,
+    utils::{call_contract, felt_to_u8, function_call},
+};
+
+pub async fn get_pool_info<P>(
+    pool_address: Felt,
+    block_id: BlockId,
+    provider: Arc<P>,
+) -> Result<SithSwapPool, AMMError>
+where
+    P: Provider + Send + Sync,
+{
+    let token_0_address = call_contract(provider.clone(), pool_address, "token0", vec![], block_id)
+        .await
+        .map_err(|_| AMMError::PoolDataError)?[0];
+
+    let token_1_address = call_contract(provider.clone(), pool_address, "token1", vec![], block_id)
+        .await
+        .map_err(|_| AMMError::PoolDataError)?[0];
+
+    let token0_decimals = call_contract(
+        provider.clone(),
+        token_0_address,
+        "decimals",
+        vec![],
+        block_id,
+    )
+    .await
+    .map_err(|_| AMMError::PoolDataError)?[0];
+
+    let token1_decimals = call_contract(
+        provider.clone(),
+        token_1_address,
+        "decimals",
+        vec![],
+        block_id,
+    )
+    .await
+    .map_err(|_| AMMError::PoolDataError)?[0];
+
+    let stable = call_contract(provider.clone(), pool_address, "stable", vec![], block_id)
+        .await
+        .map_err(|_| AMMError::PoolDataError)?[0]
+        != Felt::ZERO;
+
+    let Reserves {
+        reserve_a,
+        reserve_b,
+    } = get_reserves(
+        pool_address,
+        SithSwap::RESERVES_SELECTOR,
+        block_id,
+        provider,
+    )
+    .await?;
+
+    Ok(SithSwapPool::new(
+        pool_address,
+        token_0_address,
+        token_1_address,
+        felt_to_u8(token0_decimals),
+        felt_to_u8(token1_decimals),
+        reserve_a,
+        reserve_b,
+        // Pairs do not expose their fee, use the protocol one of their curve
+        if stable { STABLE_FEE } else { VOLATILE_FEE },
+        stable,
+    ))
+}
+
+/// Calls returning the tokens of a pool, followed by its curve, to populate many pools
+/// with batched requests.
+pub fn metadata_calls(pool_address: Felt) -> Result<Vec<FunctionCall>, AMMError> {
+    ["token0", "token1", "stable"]
+        .iter()
+        .map(|method| function_call(pool_address, method, vec![]))
+        .collect::<Result<_, _>>()
+        .map_err(|_| AMMError::PoolDataError)
+}
+
+/// Writes the results of [`metadata_calls`] into `pool`.
+pub fn set_metadata(pool: &mut SithSwapPool, results: &[Vec<Felt>]) -> Option<()> {
+    let metadata = results
+        .iter()
+        .map(|result| result.first().copied())
+        .collect::<Option<Vec<Felt>>>()?;
+    let [token_a, token_b, stable, ..] = metadata[..] else {
+        return None;
+    };
+
+    pool.token_a = token_a;
+    pool.token_b = token_b;
+    pool.stable = stable != Felt::ZERO;
+    // Pairs do not expose their fee, use the protocol one of their curve
+    pool.fee = if pool.stable {
+        STABLE_FEE
+    } else {
+        VOLATILE_FEE
+    };
+
+    Some(())
+}
@@ -0,0 +1,35 @@
+use std::sync::Arc;
+
+use starknet::{
+    core::types::{BlockId, BlockTag, Felt},
+    providers::Provider,
+};
+
+use super::get_data::get_pool_info;
+use crate::{
+    amm::solidly::pool::{SolidlyFork, SolidlyPool},
+    errors::AMMError,
+};
+
+/// SithSwap, whose pairs do not expose their fee.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SithSwap;
+
+impl SolidlyFork for SithSwap {
+    const NAME: &'static str = "SithSwap";
+    const RESERVES_SELECTOR: &'static str = "getReserves";
+}
+
+/// A SithSwap pair.
+pub type SithSwapPool = SolidlyPool<SithSwap>;
+
+impl SithSwapPool {
+    /// Reads the pair from chain, with the protocol fee of its curve. Use
+    /// [`super::factory::SithSwapFactory`] to apply other fees.
+    pub async fn new_from_address<P>(pool_address: Felt, provider: Arc<P>) -> Result<Self, AMMError>
+    where
+        P: Provider + Send + Sync,
+    {
+        get_pool_info(pool_address, BlockId::Tag(BlockTag::Latest), provider).await
+    }
+}
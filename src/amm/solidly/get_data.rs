@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use starknet::{
+    core::types::{BlockId, Felt, FunctionCall},
+    providers::Provider,
+};
+
+use crate::{
+    amm::types::{Amount, Reserves},
+    errors::AMMError,
+    utils::{call_contract, function_call},
+};
+
+/// Reads the reserves of a pair with the `reserves_selector` of its fork.
+pub async fn get_reserves<P>(
+    pool_address: Felt,
+    reserves_selector: &str,
+    block_id: BlockId,
+    provider: Arc<P>,
+) -> Result<Reserves, AMMError>
+where
+    P: Provider + Send + Sync,
+{
+    let result = call_contract(provider, pool_address, reserves_selector, vec![], block_id)
+        .await
+        .map_err(|_| AMMError::SyncError(pool_address))?;
+
+    decode_reserves(&[result]).ok_or(AMMError::SyncError(pool_address))
+}
+
+/// Calls returning the reserves of a pair, decoded by [`decode_reserves`].
+pub fn reserve_calls(
+    pool_address: Felt,
+    reserves_selector: &str,
+) -> Result<Vec<FunctionCall>, AMMError> {
+    let call = function_call(pool_address, reserves_selector, vec![])
+        .map_err(|_| AMMError::PoolDataError)?;
+
+    Ok(vec![call])
+}
+
+/// Decodes `(reserve0: u256, reserve1: u256, ...)`.
+pub fn decode_reserves(results: &[Vec<Felt>]) -> Option<Reserves> {
+    let result = results.first()?;
+    if result.len() < 4 {
+        return None;
+    }
+
+    Some(Reserves {
+        reserve_a: Amount::from_u256(result[0], result[1]).ok()?,
+        reserve_b: Amount::from_u256(result[2], result[3]).ok()?,
+    })
+}
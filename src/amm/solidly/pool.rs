@@ -0,0 +1,272 @@
+use std::{fmt::Debug, marker::PhantomData, sync::Arc};
+
+use async_trait::async_trait;
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use starknet::{
+    core::types::{BlockId, Felt, StarknetError},
+    providers::Provider,
+};
+use tracing::instrument;
+
+use super::get_data::get_reserves;
+use crate::{
+    amm::{
+        math::{ratio_to_decimal, ratio_to_f64, solidly_get_amount_in, solidly_get_amount_out},
+        pool::AutomatedMarketMaker,
+        types::{Amount, Fee, Reserves, Token},
+    },
+    errors::{AMMError, ArithmeticError, SwapSimulationError},
+};
+
+/// A Solidly fork, telling apart how [`SolidlyPool`]s of the fork are read.
+pub trait SolidlyFork: Clone + Debug + Default + Send + Sync + 'static {
+    /// Name of the fork in logs.
+    const NAME: &'static str;
+    /// Entry point of the pair returning its reserves.
+    const RESERVES_SELECTOR: &'static str;
+}
+
+/// A Solidly style pair of the fork `F`, priced with `x³y + y³x = k` when `stable` and
+/// `xy = k` otherwise.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(bound = "")]
+pub struct SolidlyPool<F: SolidlyFork> {
+    pub pool_address: Felt,
+    pub token_a: Felt,
+    pub token_b: Felt,
+    pub token_a_decimals: u8,
+    pub token_b_decimals: u8,
+    pub reserve_a: Amount,
+    pub reserve_b: Amount,
+    /// Swap fee of the pair, set by the factory of the fork.
+    pub fee: Fee,
+    pub stable: bool,
+    #[serde(skip)]
+    fork: PhantomData<F>,
+}
+
+#[async_trait]
+impl<F: SolidlyFork> AutomatedMarketMaker for SolidlyPool<F> {
+    fn address(&self) -> Felt {
+        self.pool_address
+    }
+
+    fn token_handles(&self) -> Vec<Token> {
+        vec![
+            Token::new(self.token_a, self.token_a_decimals),
+            Token::new(self.token_b, self.token_b_decimals),
+        ]
+    }
+
+    fn reserves(&self) -> Vec<Amount> {
+        vec![self.reserve_a, self.reserve_b]
+    }
+
+    #[instrument(skip(self, provider), level = "debug")]
+    async fn sync<P>(&mut self, block_id: BlockId, provider: Arc<P>) -> Result<(), StarknetError>
+    where
+        P: Provider + Send + Sync,
+    {
+        let Reserves {
+            reserve_a,
+            reserve_b,
+        } = get_reserves(self.pool_address, F::RESERVES_SELECTOR, block_id, provider).await?;
+        tracing::info!(?reserve_a, ?reserve_b, address = ?self.address(), stable = self.stable, fork = F::NAME, "Solidly sync");
+
+        self.reserve_a = reserve_a;
+        self.reserve_b = reserve_b;
+
+        Ok(())
+    }
+
+    /// Marginal price of `base_token`, i.e. `dy/dx` of the pool invariant at the current reserves.
+    fn calculate_price(&self, base_token: Felt, quote_token: Felt) -> Result<f64, AMMError> {
+        self.validate_swap_tokens(base_token, quote_token)?;
+
+        let x = self.reserve_a.to_biguint().to_f64().unwrap_or_default()
+            / 10f64.powi(self.token_a_decimals as i32);
+        let y = self.reserve_b.to_biguint().to_f64().unwrap_or_default()
+            / 10f64.powi(self.token_b_decimals as i32);
+
+        // Price of token_a in token_b: (3x²y + y³) / (x³ + 3xy²) for stable pairs
+        let (numerator, denominator) = if self.stable {
+            (3.0 * x * x * y + y * y * y, x * x * x + 3.0 * x * y * y)
+        } else {
+            (y, x)
+        };
+
+        let price = if base_token == self.token_a {
+            ratio_to_f64(numerator, denominator)
+        } else {
+            ratio_to_f64(denominator, numerator)
+        };
+
+        Ok(price?)
+    }
+
+    fn calculate_price_decimal(
+        &self,
+        base_token: Felt,
+        quote_token: Felt,
+    ) -> Result<Decimal, AMMError> {
+        self.validate_swap_tokens(base_token, quote_token)?;
+
+        // Both reserves scaled by 10^(decimals_a + decimals_b), which cancels out of the price
+        let x =
+            self.reserve_a.to_biguint() * BigUint::from(10u32).pow(self.token_b_decimals.into());
+        let y =
+            self.reserve_b.to_biguint() * BigUint::from(10u32).pow(self.token_a_decimals.into());
+
+        let (numerator, denominator) = if self.stable {
+            (
+                BigUint::from(3u32) * &x * &x * &y + &y * &y * &y,
+                &x * &x * &x + BigUint::from(3u32) * &x * &y * &y,
+            )
+        } else {
+            (y, x)
+        };
+
+        let price = if base_token == self.token_a {
+            ratio_to_decimal(&numerator, &denominator)
+        } else {
+            ratio_to_decimal(&denominator, &numerator)
+        };
+
+        price.map_err(AMMError::from)
+    }
+
+    fn simulate_swap(
+        &self,
+        token_in: Felt,
+        token_out: Felt,
+        amount_in: Amount,
+    ) -> Result<Amount, SwapSimulationError> {
+        self.validate_swap_tokens(token_in, token_out)?;
+
+        Ok(self.get_amount_out(amount_in, self.token_a == token_in)?)
+    }
+
+    /// Locally simulates a swap in the AMM.
+    /// Mutates the AMM state to the state of the AMM after swapping.
+    /// Returns the amount received for `amount_in` of `token_in`.
+    fn simulate_swap_mut(
+        &mut self,
+        token_in: Felt,
+        token_out: Felt,
+        amount_in: Amount,
+    ) -> Result<Amount, SwapSimulationError> {
+        let amount_out = self.simulate_swap(token_in, token_out, amount_in)?;
+
+        if self.token_a == token_in {
+            self.reserve_a = self.reserve_a.checked_add(amount_in)?;
+            self.reserve_b = self.reserve_b.checked_sub(amount_out)?;
+        } else {
+            self.reserve_b = self.reserve_b.checked_add(amount_in)?;
+            self.reserve_a = self.reserve_a.checked_sub(amount_out)?;
+        }
+
+        Ok(amount_out)
+    }
+
+    /// Returns the amount of `token_in` needed to receive exactly `amount_out` of
+    /// `token_out`, rounded up.
+    fn get_amount_in(
+        &self,
+        token_in: Felt,
+        token_out: Felt,
+        amount_out: Amount,
+    ) -> Result<Amount, SwapSimulationError> {
+        self.validate_swap_tokens(token_in, token_out)?;
+
+        let (reserve_in, reserve_out, decimals_in, decimals_out) = if self.token_a == token_in {
+            (
+                self.reserve_a,
+                self.reserve_b,
+                self.token_a_decimals,
+                self.token_b_decimals,
+            )
+        } else {
+            (
+                self.reserve_b,
+                self.reserve_a,
+                self.token_b_decimals,
+                self.token_a_decimals,
+            )
+        };
+
+        let amount_in = solidly_get_amount_in(
+            &amount_out.to_biguint(),
+            &reserve_in.to_biguint(),
+            &reserve_out.to_biguint(),
+            decimals_in,
+            decimals_out,
+            self.fee,
+            self.stable,
+        )?;
+
+        Ok(Amount::from_biguint(&amount_in)?)
+    }
+}
+
+impl<F: SolidlyFork> SolidlyPool<F> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        pool_address: Felt,
+        token_a: Felt,
+        token_b: Felt,
+        token_a_decimals: u8,
+        token_b_decimals: u8,
+        reserve_a: Amount,
+        reserve_b: Amount,
+        fee: Fee,
+        stable: bool,
+    ) -> SolidlyPool<F> {
+        SolidlyPool {
+            pool_address,
+            token_a,
+            token_b,
+            token_a_decimals,
+            token_b_decimals,
+            reserve_a,
+            reserve_b,
+            fee,
+            stable,
+            fork: PhantomData,
+        }
+    }
+
+    pub fn get_amount_out(
+        &self,
+        amount_in: Amount,
+        is_input_token_a: bool,
+    ) -> Result<Amount, ArithmeticError> {
+        let (reserve_in, reserve_out, decimals_in, decimals_out) = if is_input_token_a {
+            (
+                self.reserve_a,
+                self.reserve_b,
+                self.token_a_decimals,
+                self.token_b_decimals,
+            )
+        } else {
+            (
+                self.reserve_b,
+                self.reserve_a,
+                self.token_b_decimals,
+                self.token_a_decimals,
+            )
+        };
+
+        Amount::from_biguint(&solidly_get_amount_out(
+            &amount_in.to_biguint(),
+            &reserve_in.to_biguint(),
+            &reserve_out.to_biguint(),
+            decimals_in,
+            decimals_out,
+            self.fee,
+            self.stable,
+        ))
+    }
+}
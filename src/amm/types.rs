@@ -1,8 +1,349 @@
-use starknet::core::types::Felt;
+use std::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+use num_bigint::BigUint;
+use num_traits::{ToPrimitive, Zero};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use starknet::core::types::{Felt, U256};
+
+use crate::errors::ArithmeticError;
 
 #[derive(Debug)]
 pub struct Reserves {
-    pub reserve_a: Felt,
-    pub reserve_b: Felt,
+    pub reserve_a: Amount,
+    pub reserve_b: Amount,
     // pub block_timestamp_last: BigUint,
 }
+
+/// Uniquely identifies a pool, including pools that share a single contract.
+///
+/// `key` is empty for AMMs that deploy one contract per pool.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct PoolId {
+    pub address: Felt,
+    pub key: Vec<Felt>,
+}
+
+impl PoolId {
+    pub fn new(address: Felt, key: Vec<Felt>) -> PoolId {
+        PoolId { address, key }
+    }
+}
+
+/// A token of a pool, with the decimals its amounts are expressed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Token {
+    pub address: Felt,
+    pub decimals: u8,
+}
+
+impl Token {
+    pub fn new(address: Felt, decimals: u8) -> Token {
+        Token { address, decimals }
+    }
+
+    /// `amount` in whole tokens, e.g. `1.5` for `1_500_000` of a token with 6 decimals.
+    pub fn to_units(&self, amount: Amount) -> f64 {
+        amount.to_biguint().to_f64().unwrap_or(f64::INFINITY) / 10f64.powi(self.decimals as i32)
+    }
+}
+
+/// A token amount, as a Cairo `u256`.
+///
+/// Wraps [`U256`] with checked arithmetic: results that do not fit in 256 bits fail with an
+/// [`ArithmeticError`] instead of wrapping around the field prime like [`Felt`] does.
+/// Serialized as a hex string, like [`Felt`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Amount(U256);
+
+impl Amount {
+    pub const ZERO: Amount = Amount::from_words(0, 0);
+    pub const MAX: Amount = Amount::from_words(u128::MAX, u128::MAX);
+
+    pub const fn from_words(low: u128, high: u128) -> Amount {
+        Amount(U256::from_words(low, high))
+    }
+
+    pub const fn low(&self) -> u128 {
+        self.0.low()
+    }
+
+    pub const fn high(&self) -> u128 {
+        self.0.high()
+    }
+
+    pub const fn is_zero(&self) -> bool {
+        self.low() == 0 && self.high() == 0
+    }
+
+    /// Decodes a Cairo `u256` from its low and high limbs, failing if a limb does not fit
+    /// in 128 bits.
+    pub fn from_u256(low: Felt, high: Felt) -> Result<Amount, ArithmeticError> {
+        Ok(Amount::from_words(felt_to_u128(low)?, felt_to_u128(high)?))
+    }
+
+    /// Decodes an amount returned as a single felt, which always fits.
+    pub fn from_felt(value: Felt) -> Amount {
+        let bytes = value.to_bytes_be();
+        Amount::from_words(
+            u128::from_be_bytes(bytes[16..].try_into().unwrap()),
+            u128::from_be_bytes(bytes[..16].try_into().unwrap()),
+        )
+    }
+
+    /// Encodes the amount as `u256` calldata, low limb first.
+    pub fn to_calldata(&self) -> [Felt; 2] {
+        [Felt::from(self.low()), Felt::from(self.high())]
+    }
+
+    pub fn to_biguint(&self) -> BigUint {
+        (BigUint::from(self.high()) << 128u32) + BigUint::from(self.low())
+    }
+
+    /// Fails with `ShadowOverflow` if `value` does not fit in 256 bits.
+    pub fn from_biguint(value: &BigUint) -> Result<Amount, ArithmeticError> {
+        let mut digits = value.iter_u64_digits();
+        let mut word = || digits.next().unwrap_or_default() as u128;
+        let low = word() | (word() << 64);
+        let high = word() | (word() << 64);
+
+        if value.bits() > 256 {
+            return Err(ArithmeticError::ShadowOverflow(U256::from_words(low, high)));
+        }
+
+        Ok(Amount::from_words(low, high))
+    }
+
+    pub fn checked_add(self, rhs: Amount) -> Result<Amount, ArithmeticError> {
+        let (low, carry) = self.low().overflowing_add(rhs.low());
+        self.high()
+            .checked_add(rhs.high())
+            .and_then(|high| high.checked_add(carry as u128))
+            .map(|high| Amount::from_words(low, high))
+            .ok_or(ArithmeticError::ShadowOverflow(U256::from_words(
+                low,
+                self.high()
+                    .wrapping_add(rhs.high())
+                    .wrapping_add(carry as u128),
+            )))
+    }
+
+    pub fn checked_sub(self, rhs: Amount) -> Result<Amount, ArithmeticError> {
+        let (low, borrow) = self.low().overflowing_sub(rhs.low());
+        self.high()
+            .checked_sub(rhs.high())
+            .and_then(|high| high.checked_sub(borrow as u128))
+            .map(|high| Amount::from_words(low, high))
+            .ok_or(ArithmeticError::AmountUnderflow)
+    }
+}
+
+fn felt_to_u128(value: Felt) -> Result<u128, ArithmeticError> {
+    let bytes = value.to_bytes_be();
+    if bytes[..16].iter().any(|byte| *byte != 0) {
+        return Err(ArithmeticError::U128ConversionError);
+    }
+
+    Ok(u128::from_be_bytes(bytes[16..].try_into().unwrap()))
+}
+
+impl Default for Amount {
+    fn default() -> Amount {
+        Amount::ZERO
+    }
+}
+
+// Ordered and hashed by value, high limb first
+impl Ord for Amount {
+    fn cmp(&self, other: &Amount) -> Ordering {
+        (self.high(), self.low()).cmp(&(other.high(), other.low()))
+    }
+}
+
+impl PartialOrd for Amount {
+    fn partial_cmp(&self, other: &Amount) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Hash for Amount {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.high(), self.low()).hash(state);
+    }
+}
+
+impl From<U256> for Amount {
+    fn from(value: U256) -> Amount {
+        Amount(value)
+    }
+}
+
+impl From<Amount> for U256 {
+    fn from(value: Amount) -> U256 {
+        value.0
+    }
+}
+
+impl From<u128> for Amount {
+    fn from(value: u128) -> Amount {
+        Amount::from_words(value, 0)
+    }
+}
+
+impl From<u64> for Amount {
+    fn from(value: u64) -> Amount {
+        Amount::from(value as u128)
+    }
+}
+
+impl From<u32> for Amount {
+    fn from(value: u32) -> Amount {
+        Amount::from(value as u128)
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_biguint())
+    }
+}
+
+impl fmt::LowerHex for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.to_biguint(), f)
+    }
+}
+
+impl Serialize for Amount {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&format!("{:#x}", self))
+    }
+}
+
+impl<'de> Deserialize<'de> for Amount {
+    /// Reads a `0x` prefixed hex string, as written for amounts and felts, or a decimal string.
+    fn deserialize<D>(deserializer: D) -> Result<Amount, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        let parsed = match value.strip_prefix("0x") {
+            Some(hex) => BigUint::parse_bytes(hex.as_bytes(), 16),
+            None => BigUint::parse_bytes(value.as_bytes(), 10),
+        };
+
+        let parsed = parsed.ok_or_else(|| de::Error::custom(format!("invalid amount {value}")))?;
+        Amount::from_biguint(&parsed).map_err(de::Error::custom)
+    }
+}
+
+/// A swap fee, as the share of the input amount kept by the pool.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Fee {
+    /// In basis points, `1 / 10_000`, as charged by Uniswap V2 style and Solidly pairs.
+    Bps(u32),
+    /// In hundredths of a basis point, `1 / 1_000_000`, as charged by Uniswap V3 style pools.
+    Pips(u32),
+    /// As a 0.128 fixed point fraction, as charged by Ekubo pools.
+    X128(u128),
+}
+
+impl Default for Fee {
+    fn default() -> Fee {
+        Fee::Bps(0)
+    }
+}
+
+impl Fee {
+    pub fn numerator(&self) -> BigUint {
+        match *self {
+            Fee::Bps(fee) | Fee::Pips(fee) => BigUint::from(fee),
+            Fee::X128(fee) => BigUint::from(fee),
+        }
+    }
+
+    pub fn denominator(&self) -> BigUint {
+        match self {
+            Fee::Bps(_) => BigUint::from(10_000u32),
+            Fee::Pips(_) => BigUint::from(1_000_000u32),
+            Fee::X128(_) => BigUint::from(1u32) << 128u32,
+        }
+    }
+
+    /// `denominator - numerator`, the share of the input that is actually swapped. Zero for
+    /// fees of 100% or more.
+    pub fn complement(&self) -> BigUint {
+        let (numerator, denominator) = (self.numerator(), self.denominator());
+        if numerator >= denominator {
+            BigUint::zero()
+        } else {
+            denominator - numerator
+        }
+    }
+
+    /// The fee as a fraction of the input, e.g. `0.003` for 30 bps.
+    pub fn to_f64(&self) -> f64 {
+        match *self {
+            Fee::Bps(fee) => fee as f64 / 10_000.0,
+            Fee::Pips(fee) => fee as f64 / 1_000_000.0,
+            Fee::X128(fee) => fee as f64 / 2f64.powi(128),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn amounts_are_ordered_by_value() {
+        let small = Amount::from_words(u128::MAX, 0);
+        let large = Amount::from_words(0, 1);
+
+        assert!(small < large);
+        assert_eq!(small.checked_add(Amount::from(1u32)).unwrap(), large);
+        assert_eq!(large.checked_sub(Amount::from(1u32)).unwrap(), small);
+        assert_eq!(U256::from(large), U256::from_words(0, 1));
+    }
+
+    #[test]
+    fn amount_overflow_is_an_error() {
+        assert!(matches!(
+            Amount::MAX.checked_add(Amount::from(1u32)),
+            Err(ArithmeticError::ShadowOverflow(_))
+        ));
+        assert!(matches!(
+            Amount::ZERO.checked_sub(Amount::from(1u32)),
+            Err(ArithmeticError::AmountUnderflow)
+        ));
+        assert!(matches!(
+            Amount::from_biguint(&(Amount::MAX.to_biguint() + 1u32)),
+            Err(ArithmeticError::ShadowOverflow(_))
+        ));
+    }
+
+    #[test]
+    fn amounts_serialize_as_hex() {
+        let amount = Amount::from_words(1, 1);
+        let json = serde_json::to_string(&amount).unwrap();
+
+        assert_eq!(json, "\"0x100000000000000000000000000000001\"");
+        assert_eq!(serde_json::from_str::<Amount>(&json).unwrap(), amount);
+        assert_eq!(
+            serde_json::from_str::<Amount>("\"1000\"").unwrap(),
+            Amount::from(1_000u32)
+        );
+    }
+
+    #[test]
+    fn token_amounts_in_whole_units() {
+        let usdc = Token::new(Felt::from_hex_unchecked("0x1"), 6);
+        assert_eq!(usdc.to_units(Amount::from(1_500_000u32)), 1.5);
+    }
+}
@@ -0,0 +1,122 @@
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use starknet::core::types::Felt;
+
+use crate::amm::types::{Amount, Fee};
+
+/// How a pair returns its reserves.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReserveLayout {
+    /// `(reserve0: felt, reserve1: felt, ...)`, as returned by 10KSwap.
+    Felt,
+    /// `(reserve0: u256, reserve1: u256, ...)`, as returned by JediSwap and StarkDeFi.
+    U256,
+}
+
+impl ReserveLayout {
+    /// Decodes `(reserve0, reserve1)` from the result of the reserves call.
+    pub fn decode(&self, result: &[Felt]) -> Option<(Amount, Amount)> {
+        match self {
+            ReserveLayout::Felt if result.len() >= 2 => {
+                Some((Amount::from_felt(result[0]), Amount::from_felt(result[1])))
+            }
+            ReserveLayout::U256 if result.len() >= 4 => Some((
+                Amount::from_u256(result[0], result[1]).ok()?,
+                Amount::from_u256(result[2], result[3]).ok()?,
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// How a factory lists the pairs it created.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum PairEnumeration {
+    /// One call returning every pair as a Cairo array.
+    Array { all_pairs: Cow<'static, str> },
+    /// A pair count followed by one call per pair index.
+    Indexed {
+        all_pairs_length: Cow<'static, str>,
+        all_pairs: Cow<'static, str>,
+    },
+}
+
+/// Router of a fork, used to cross-check local quotes against the chain.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct V2Router {
+    pub address: Felt,
+    /// Entry point taking `(amount_in: u256, path: Array<felt>)` and returning the amount
+    /// after every hop of the path.
+    pub get_amounts_out: Cow<'static, str>,
+}
+
+/// Describes a Uniswap V2 fork, so that [`super::pool::UniswapV2Pool`] and
+/// [`super::factory::UniswapV2Factory`] can sync any of them.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct V2Descriptor {
+    pub name: Cow<'static, str>,
+    /// Entry point of the pair returning its reserves.
+    pub reserves_selector: Cow<'static, str>,
+    pub reserve_layout: ReserveLayout,
+    /// Swap fee of the pairs.
+    pub fee: Fee,
+    pub pair_enumeration: PairEnumeration,
+    /// Entry point of the factory returning the pair of two tokens, zero when there is none.
+    #[serde(default = "default_get_pair")]
+    pub get_pair: Cow<'static, str>,
+    #[serde(default)]
+    pub router: Option<V2Router>,
+}
+
+fn default_get_pair() -> Cow<'static, str> {
+    Cow::Borrowed("get_pair")
+}
+
+pub const JEDISWAP: V2Descriptor = V2Descriptor {
+    name: Cow::Borrowed("JediSwap"),
+    reserves_selector: Cow::Borrowed("get_reserves"),
+    reserve_layout: ReserveLayout::U256,
+    fee: Fee::Bps(30),
+    pair_enumeration: PairEnumeration::Array {
+        all_pairs: Cow::Borrowed("get_all_pairs"),
+    },
+    get_pair: Cow::Borrowed("get_pair"),
+    router: Some(V2Router {
+        address: Felt::from_hex_unchecked(
+            "0x041fd22b238fa21cfcf5dd45a8548974d8263b3a531a60388411c5e230f97023",
+        ),
+        get_amounts_out: Cow::Borrowed("get_amounts_out"),
+    }),
+};
+
+pub const TENKSWAP: V2Descriptor = V2Descriptor {
+    name: Cow::Borrowed("10KSwap"),
+    reserves_selector: Cow::Borrowed("getReserves"),
+    reserve_layout: ReserveLayout::Felt,
+    fee: Fee::Bps(30),
+    pair_enumeration: PairEnumeration::Indexed {
+        all_pairs_length: Cow::Borrowed("allPairsLength"),
+        all_pairs: Cow::Borrowed("allPairs"),
+    },
+    get_pair: Cow::Borrowed("getPair"),
+    router: Some(V2Router {
+        address: Felt::from_hex_unchecked(
+            "0x07a6f98c03379b9513ca84cca1373ff452a7462a3b61598f0af5bb27ad7f76d1",
+        ),
+        get_amounts_out: Cow::Borrowed("getAmountsOut"),
+    }),
+};
+
+pub const STARKDEFI: V2Descriptor = V2Descriptor {
+    name: Cow::Borrowed("StarkDeFi"),
+    reserves_selector: Cow::Borrowed("get_reserves"),
+    reserve_layout: ReserveLayout::U256,
+    fee: Fee::Bps(30),
+    pair_enumeration: PairEnumeration::Indexed {
+        all_pairs_length: Cow::Borrowed("all_pairs_length"),
+        all_pairs: Cow::Borrowed("all_pairs"),
+    },
+    get_pair: Cow::Borrowed("get_pair"),
+    router: None,
+};
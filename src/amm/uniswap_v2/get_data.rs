@@ -0,0 +1,183 @@
+use std::sync::Arc;
+
+use starknet::{
+    core::types::{BlockId, Felt, FunctionCall},
+    providers::Provider,
+};
+
+use super::{
+    descriptor::{PairEnumeration, V2Descriptor},
+    pool::UniswapV2Pool,
+};
+use crate::{
+    amm::types::Reserves,
+    errors::AMMError,
+    utils::{call_contract, decode_felt_array, felt_to_u128, felt_to_u8, function_call},
+};
+
+pub async fn get_pool_info<P>(
+    pool_address: Felt,
+    descriptor: &V2Descriptor,
+    block_id: BlockId,
+    provider: Arc<P>,
+) -> Result<UniswapV2Pool, AMMError>
+where
+    P: Provider + Send + Sync,
+{
+    let token_0_address = call_contract(provider.clone(), pool_address, "token0", vec![], block_id)
+        .await
+        .map_err(|_| AMMError::PoolDataError)?[0];
+
+    let token_1_address = call_contract(provider.clone(), pool_address, "token1", vec![], block_id)
+        .await
+        .map_err(|_| AMMError::PoolDataError)?[0];
+
+    let token0_decimals = call_contract(
+        provider.clone(),
+        token_0_address,
+        "decimals",
+        vec![],
+        block_id,
+    )
+    .await
+    .map_err(|_| AMMError::PoolDataError)?[0];
+
+    let token1_decimals = call_contract(
+        provider.clone(),
+        token_1_address,
+        "decimals",
+        vec![],
+        block_id,
+    )
+    .await
+    .map_err(|_| AMMError::PoolDataError)?[0];
+
+    let Reserves {
+        reserve_a,
+        reserve_b,
+    } = get_reserves(pool_address, descriptor, block_id, provider).await?;
+
+    Ok(UniswapV2Pool::new(
+        pool_address,
+        token_0_address,
+        token_1_address,
+        felt_to_u8(token0_decimals),
+        felt_to_u8(token1_decimals),
+        reserve_a,
+        reserve_b,
+        descriptor.clone(),
+    ))
+}
+
+pub async fn get_reserves<P>(
+    pool_address: Felt,
+    descriptor: &V2Descriptor,
+    block_id: BlockId,
+    provider: Arc<P>,
+) -> Result<Reserves, AMMError>
+where
+    P: Provider + Send + Sync,
+{
+    let result = call_contract(
+        provider,
+        pool_address,
+        &descriptor.reserves_selector,
+        vec![],
+        block_id,
+    )
+    .await
+    .map_err(|_| AMMError::SyncError(pool_address))?;
+
+    decode_reserves(descriptor, &[result]).ok_or(AMMError::SyncError(pool_address))
+}
+
+/// Calls returning the tokens of a pair, to populate many pairs with batched requests.
+pub fn metadata_calls(pool_address: Felt) -> Result<Vec<FunctionCall>, AMMError> {
+    ["token0", "token1"]
+        .iter()
+        .map(|method| function_call(pool_address, method, vec![]))
+        .collect::<Result<_, _>>()
+        .map_err(|_| AMMError::PoolDataError)
+}
+
+/// Writes the results of [`metadata_calls`] into `pool`.
+pub fn set_metadata(pool: &mut UniswapV2Pool, results: &[Vec<Felt>]) -> Option<()> {
+    pool.token_a = *results.first()?.first()?;
+    pool.token_b = *results.get(1)?.first()?;
+    // Pairs do not expose their fee, use the protocol one
+    pool.fee = pool.descriptor.fee;
+
+    Some(())
+}
+
+/// Calls returning the reserves of a pair, decoded by [`decode_reserves`].
+pub fn reserve_calls(
+    pool_address: Felt,
+    descriptor: &V2Descriptor,
+) -> Result<Vec<FunctionCall>, AMMError> {
+    let call = function_call(pool_address, &descriptor.reserves_selector, vec![])
+        .map_err(|_| AMMError::PoolDataError)?;
+
+    Ok(vec![call])
+}
+
+pub fn decode_reserves(descriptor: &V2Descriptor, results: &[Vec<Felt>]) -> Option<Reserves> {
+    let (reserve_a, reserve_b) = descriptor.reserve_layout.decode(results.first()?)?;
+
+    Some(Reserves {
+        reserve_a,
+        reserve_b,
+    })
+}
+
+/// Returns the address of every pair created by the factory up to `block_id`.
+pub async fn get_all_pairs<P>(
+    factory_address: Felt,
+    descriptor: &V2Descriptor,
+    block_id: BlockId,
+    provider: Arc<P>,
+) -> Result<Vec<Felt>, AMMError>
+where
+    P: Provider + Send + Sync,
+{
+    match &descriptor.pair_enumeration {
+        PairEnumeration::Array { all_pairs } => {
+            let result = call_contract(provider, factory_address, all_pairs, vec![], block_id)
+                .await
+                .map_err(|_| AMMError::PoolDataError)?;
+
+            Ok(decode_felt_array(&result)
+                .ok_or(AMMError::PoolDataError)?
+                .to_vec())
+        }
+        PairEnumeration::Indexed {
+            all_pairs_length,
+            all_pairs,
+        } => {
+            let pairs_length = call_contract(
+                provider.clone(),
+                factory_address,
+                all_pairs_length,
+                vec![],
+                block_id,
+            )
+            .await
+            .map_err(|_| AMMError::PoolDataError)?[0];
+
+            let mut pairs = vec![];
+            for idx in 0..felt_to_u128(pairs_length) as u64 {
+                let pair = call_contract(
+                    provider.clone(),
+                    factory_address,
+                    all_pairs,
+                    vec![Felt::from(idx)],
+                    block_id,
+                )
+                .await
+                .map_err(|_| AMMError::PoolDataError)?[0];
+                pairs.push(pair);
+            }
+            Ok(pairs)
+        }
+    }
+}
@@ -0,0 +1,148 @@
+//! Checkpoint formats of the JediSwap and 10KSwap pools and factories from before they were
+//! [`UniswapV2Pool`]s and [`UniswapV2Factory`]s, so that older checkpoints still load.
+
+use serde::Deserialize;
+use starknet::core::types::Felt;
+
+use super::{
+    descriptor::{V2Descriptor, JEDISWAP, TENKSWAP},
+    factory::UniswapV2Factory,
+    pool::UniswapV2Pool,
+};
+use crate::amm::{
+    factory::Factory,
+    pool::AMM,
+    types::{Amount, Fee},
+};
+
+/// A `JediswapFactory` or `TenKFactory` of an older checkpoint.
+#[derive(Deserialize)]
+pub struct LegacyV2Factory {
+    factory_address: Felt,
+}
+
+impl LegacyV2Factory {
+    pub fn into_jediswap(self) -> Factory {
+        Factory::UniswapV2Factory(UniswapV2Factory::new(self.factory_address, JEDISWAP))
+    }
+
+    pub fn into_tenkswap(self) -> Factory {
+        Factory::UniswapV2Factory(UniswapV2Factory::new(self.factory_address, TENKSWAP))
+    }
+}
+
+/// A `JediswapPool` or `TenkSwapPool` of an older checkpoint. Reserves were written as felts,
+/// which read as amounts.
+#[derive(Deserialize)]
+pub struct LegacyV2Pool {
+    pool_address: Felt,
+    token_a: Felt,
+    token_b: Felt,
+    token_a_decimals: u8,
+    token_b_decimals: u8,
+    reserve_a: Amount,
+    reserve_b: Amount,
+    fee: LegacyFee,
+}
+
+/// Fees were first written as a bare integer that pools never set, then as a [`Fee`].
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LegacyFee {
+    Fee(Fee),
+    Unset(u32),
+}
+
+impl LegacyV2Pool {
+    pub fn into_jediswap(self) -> AMM {
+        AMM::UniswapV2Pool(self.into_pool(JEDISWAP))
+    }
+
+    pub fn into_tenkswap(self) -> AMM {
+        AMM::UniswapV2Pool(self.into_pool(TENKSWAP))
+    }
+
+    /// Converts the pool to a pair of the fork described by `descriptor`, keeping its fee
+    /// unless it was never set.
+    fn into_pool(self, descriptor: V2Descriptor) -> UniswapV2Pool {
+        let mut pool = UniswapV2Pool::new(
+            self.pool_address,
+            self.token_a,
+            self.token_b,
+            self.token_a_decimals,
+            self.token_b_decimals,
+            self.reserve_a,
+            self.reserve_b,
+            descriptor,
+        );
+        if let LegacyFee::Fee(fee) = self.fee {
+            pool.fee = fee;
+        }
+
+        pool
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_pools_load_as_uniswap_v2_pools() {
+        let json = r#"{"JediswapPool":{"pool_address":"0x1","token_a":"0xa","token_b":"0xb",
+            "token_a_decimals":18,"token_b_decimals":6,"reserve_a":"0x3e8","reserve_b":"0x7d0",
+            "fee":0}}"#;
+        let AMM::UniswapV2Pool(pool) = serde_json::from_str::<AMM>(json).unwrap() else {
+            panic!("legacy JediSwap pool did not load as a UniswapV2Pool");
+        };
+        assert_eq!(pool.pool_address, Felt::ONE);
+        assert_eq!(pool.reserve_a, Amount::from_words(1000, 0));
+        assert_eq!(pool.reserve_b, Amount::from_words(2000, 0));
+        assert_eq!(pool.fee, JEDISWAP.fee);
+        assert_eq!(pool.descriptor, JEDISWAP);
+
+        let json = r#"{"TenkSwapPool":{"pool_address":"0x2","token_a":"0xa","token_b":"0xb",
+            "token_a_decimals":18,"token_b_decimals":18,"reserve_a":"0x1","reserve_b":"0x1",
+            "fee":{"Bps":25}}}"#;
+        let AMM::UniswapV2Pool(pool) = serde_json::from_str::<AMM>(json).unwrap() else {
+            panic!("legacy 10KSwap pool did not load as a UniswapV2Pool");
+        };
+        assert_eq!(pool.fee, Fee::Bps(25));
+        assert_eq!(pool.descriptor, TENKSWAP);
+    }
+
+    #[test]
+    fn legacy_factories_load_as_uniswap_v2_factories() {
+        for (json, descriptor) in [
+            (r#"{"JediswapFactory":{"factory_address":"0x1"}}"#, JEDISWAP),
+            (r#"{"TenKFactory":{"factory_address":"0x1"}}"#, TENKSWAP),
+        ] {
+            let Factory::UniswapV2Factory(factory) = serde_json::from_str::<Factory>(json).unwrap()
+            else {
+                panic!("legacy factory did not load as a UniswapV2Factory");
+            };
+            assert_eq!(factory.factory_address, Felt::ONE);
+            assert_eq!(factory.descriptor, descriptor);
+        }
+    }
+
+    #[test]
+    fn current_pools_still_round_trip() {
+        let pool = UniswapV2Pool::new(
+            Felt::ONE,
+            Felt::from_hex_unchecked("0xa"),
+            Felt::from_hex_unchecked("0xb"),
+            18,
+            18,
+            Amount::from_words(1, 0),
+            Amount::from_words(2, 0),
+            JEDISWAP,
+        );
+        let json = serde_json::to_string(&AMM::UniswapV2Pool(pool)).unwrap();
+        let AMM::UniswapV2Pool(pool) = serde_json::from_str::<AMM>(&json).unwrap() else {
+            panic!("UniswapV2Pool did not round trip");
+        };
+        assert_eq!(pool.reserve_b, Amount::from_words(2, 0));
+        assert_eq!(pool.descriptor, JEDISWAP);
+    }
+}
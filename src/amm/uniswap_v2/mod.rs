@@ -0,0 +1,5 @@
+pub mod descriptor;
+pub mod factory;
+pub mod get_data;
+pub mod legacy;
+pub mod pool;
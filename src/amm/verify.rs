@@ -0,0 +1,265 @@
+use std::sync::Arc;
+
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
+use starknet::{
+    core::types::{BlockId, Felt},
+    providers::Provider,
+};
+
+use super::{
+    math::constant_product_amount_out,
+    pool::{AutomatedMarketMaker, AMM},
+    types::{Amount, Fee, PoolId},
+};
+use crate::{
+    errors::AMMError,
+    utils::{call_contract, felt_to_u8},
+};
+
+/// Fees are expressed in basis points.
+const FEE_DENOMINATOR: u32 = 10_000;
+
+/// Result of comparing a local quote of a pool with the quote of its router.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuoteCheck {
+    pub pool: PoolId,
+    /// Block both quotes were computed at.
+    pub block_number: u64,
+    pub token_in: Felt,
+    pub token_out: Felt,
+    pub amount_in: Amount,
+    /// Output of [`AutomatedMarketMaker::simulate_swap`] on the pool state at `block_number`.
+    pub local_amount_out: Amount,
+    /// Output of the router `get_amounts_out` at `block_number`.
+    pub onchain_amount_out: Amount,
+    /// `(local - onchain) / onchain` in basis points, positive when the local quote is
+    /// too optimistic.
+    pub deviation_bps: f64,
+    pub issues: Vec<QuoteIssue>,
+}
+
+impl QuoteCheck {
+    /// True when the local quote matches the chain and the pool is configured correctly.
+    pub fn is_consistent(&self) -> bool {
+        self.local_amount_out == self.onchain_amount_out && self.issues.is_empty()
+    }
+}
+
+/// Misconfiguration of a pool found while cross-checking its quotes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuoteIssue {
+    /// The pool fee does not reproduce the router quote. `implied` is the fee, to the basis
+    /// point, that does, if any.
+    FeeMismatch {
+        configured: Fee,
+        implied: Option<Fee>,
+    },
+    /// The pool decimals of `token` differ from the token contract.
+    DecimalsMismatch {
+        token: Felt,
+        configured: u8,
+        onchain: u8,
+    },
+}
+
+/// Quotes swapping `amount_in` of `token_in` for `token_out` both locally and with the DEX
+/// router at the latest block, and reports how far apart they are.
+///
+/// Only Uniswap V2 style pools whose descriptor has a router can be checked. The pool passed
+/// in is left untouched, the local quote is computed on a copy holding the reserves of the
+/// same block as the router quote.
+pub async fn verify_quote<P>(
+    amm: &AMM,
+    token_in: Felt,
+    token_out: Felt,
+    amount_in: Amount,
+    provider: Arc<P>,
+) -> Result<QuoteCheck, AMMError>
+where
+    P: Provider + Send + Sync,
+{
+    let block_number = provider.block_number().await?;
+    verify_quote_at(amm, token_in, token_out, amount_in, block_number, provider).await
+}
+
+/// Cross-checks every pool of `amms` swapping `amount_in` of its first token for its second,
+/// all at the same block. Returns one result per pool, in order.
+pub async fn verify_quotes<P>(
+    amms: &[AMM],
+    amount_in: Amount,
+    provider: Arc<P>,
+) -> Result<Vec<(PoolId, Result<QuoteCheck, AMMError>)>, AMMError>
+where
+    P: Provider + Send + Sync,
+{
+    let block_number = provider.block_number().await?;
+
+    let mut checks = Vec::with_capacity(amms.len());
+    for amm in amms {
+        let tokens = amm.tokens();
+        let check = verify_quote_at(
+            amm,
+            tokens[0],
+            tokens[1],
+            amount_in,
+            block_number,
+            provider.clone(),
+        )
+        .await;
+        checks.push((amm.id(), check));
+    }
+
+    Ok(checks)
+}
+
+async fn verify_quote_at<P>(
+    amm: &AMM,
+    token_in: Felt,
+    token_out: Felt,
+    amount_in: Amount,
+    block_number: u64,
+    provider: Arc<P>,
+) -> Result<QuoteCheck, AMMError>
+where
+    P: Provider + Send + Sync,
+{
+    let AMM::UniswapV2Pool(pool) = amm else {
+        return Err(AMMError::NoQuoteRouter(amm.address()));
+    };
+    let router = pool
+        .descriptor
+        .router
+        .as_ref()
+        .ok_or(AMMError::NoQuoteRouter(pool.pool_address))?;
+    pool.validate_swap_tokens(token_in, token_out)?;
+
+    let block_id = BlockId::Number(block_number);
+
+    let reserves = call_contract(
+        provider.clone(),
+        pool.pool_address,
+        &pool.descriptor.reserves_selector,
+        vec![],
+        block_id,
+    )
+    .await
+    .map_err(|_| AMMError::SyncError(pool.pool_address))?;
+    let (reserve_a, reserve_b) = pool
+        .descriptor
+        .reserve_layout
+        .decode(&reserves)
+        .ok_or(AMMError::SyncError(pool.pool_address))?;
+
+    let mut local_pool = pool.clone();
+    local_pool.reserve_a = reserve_a;
+    local_pool.reserve_b = reserve_b;
+    let local_amount_out = local_pool.simulate_swap(token_in, token_out, amount_in)?;
+
+    // get_amounts_out(amount_in: u256, path: Array<felt>) -> Array<u256>
+    let [low, high] = amount_in.to_calldata();
+    let amounts = call_contract(
+        provider.clone(),
+        router.address,
+        &router.get_amounts_out,
+        vec![low, high, Felt::TWO, token_in, token_out],
+        block_id,
+    )
+    .await
+    .map_err(|_| AMMError::RouterQuoteError(pool.pool_address))?;
+    if amounts.len() < 5 {
+        return Err(AMMError::RouterQuoteError(pool.pool_address));
+    }
+    let onchain_amount_out =
+        Amount::from_u256(amounts[amounts.len() - 2], amounts[amounts.len() - 1])?;
+
+    let mut issues = vec![];
+
+    if local_amount_out != onchain_amount_out {
+        let (reserve_in, reserve_out) = if token_in == pool.token_a {
+            (reserve_a, reserve_b)
+        } else {
+            (reserve_b, reserve_a)
+        };
+        let implied = implied_fee(
+            &amount_in.to_biguint(),
+            &reserve_in.to_biguint(),
+            &reserve_out.to_biguint(),
+            &onchain_amount_out.to_biguint(),
+        );
+        if implied != Some(pool.fee) {
+            issues.push(QuoteIssue::FeeMismatch {
+                configured: pool.fee,
+                implied,
+            });
+        }
+    }
+
+    for token in pool.token_handles() {
+        let decimals = call_contract(
+            provider.clone(),
+            token.address,
+            "decimals",
+            vec![],
+            block_id,
+        )
+        .await
+        .map_err(|_| AMMError::PoolDataError)?;
+        let onchain = felt_to_u8(*decimals.first().ok_or(AMMError::PoolDataError)?);
+        if onchain != token.decimals {
+            issues.push(QuoteIssue::DecimalsMismatch {
+                token: token.address,
+                configured: token.decimals,
+                onchain,
+            });
+        }
+    }
+
+    let local = local_amount_out.to_biguint().to_f64().unwrap_or_default();
+    let onchain = onchain_amount_out.to_biguint().to_f64().unwrap_or_default();
+    let deviation_bps = if onchain_amount_out.is_zero() {
+        if local_amount_out.is_zero() {
+            0.0
+        } else {
+            f64::INFINITY
+        }
+    } else {
+        (local - onchain) / onchain * FEE_DENOMINATOR as f64
+    };
+
+    Ok(QuoteCheck {
+        pool: amm.id(),
+        block_number,
+        token_in,
+        token_out,
+        amount_in,
+        local_amount_out,
+        onchain_amount_out,
+        deviation_bps,
+        issues,
+    })
+}
+
+/// Searches the fee, in basis points, for which a constant product swap returns exactly
+/// `amount_out`. The output only decreases as the fee grows, so this is a binary search.
+fn implied_fee(
+    amount_in: &BigUint,
+    reserve_in: &BigUint,
+    reserve_out: &BigUint,
+    amount_out: &BigUint,
+) -> Option<Fee> {
+    let amount_out_at =
+        |fee| constant_product_amount_out(amount_in, reserve_in, reserve_out, Fee::Bps(fee));
+
+    let (mut low, mut high) = (0, FEE_DENOMINATOR);
+    while low < high {
+        let fee = (low + high) / 2;
+        if &amount_out_at(fee) > amount_out {
+            low = fee + 1;
+        } else {
+            high = fee;
+        }
+    }
+
+    (&amount_out_at(low) == amount_out).then_some(Fee::Bps(low))
+}
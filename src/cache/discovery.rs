@@ -0,0 +1,394 @@
+use std::{ops::RangeInclusive, sync::Arc};
+
+use futures::{future, stream, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use starknet::{
+    core::types::{BlockId, EmittedEvent, Felt},
+    providers::Provider,
+};
+use thiserror::Error;
+
+use crate::{
+    amm::{
+        batch::populate_amms_with_report,
+        factory::{
+            get_events_in_range, AutomatedMarketMakerFactory, Factory,
+            MAX_CONCURRENT_EVENT_REQUESTS,
+        },
+        myswap::{
+            factory::MySwapFactory,
+            get_data::{get_pool_count, get_pool_info},
+        },
+        pool::AMM,
+    },
+    errors::AMMError,
+};
+
+/// Position of a discovery, to resume it after a restart.
+///
+/// Pools found from creation events are ordered by block, `pair_index` being the number of
+/// pools of `block` already yielded. mySwap pools are ordered by pool id, `pair_index` being
+/// the last id yielded.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DiscoveryCursor {
+    pub factory: Felt,
+    pub block: u64,
+    pub pair_index: u64,
+}
+
+impl DiscoveryCursor {
+    /// Cursor discovering every pool of `factory` created from `block`.
+    pub fn new(factory: Felt, block: u64) -> DiscoveryCursor {
+        DiscoveryCursor {
+            factory,
+            block,
+            pair_index: 0,
+        }
+    }
+
+    /// Cursor resuming right before the item yielded with this cursor, so that the item is
+    /// discovered again.
+    fn before(self) -> DiscoveryCursor {
+        DiscoveryCursor {
+            pair_index: self.pair_index.saturating_sub(1),
+            ..self
+        }
+    }
+}
+
+/// A populated pool, with the cursor resuming discovery right after it.
+#[derive(Debug, Clone)]
+pub struct DiscoveredPool {
+    pub amm: AMM,
+    pub cursor: DiscoveryCursor,
+}
+
+/// A pool, or a range of blocks, that could not be discovered. Resuming from `cursor` retries
+/// it.
+#[derive(Error, Debug)]
+#[error("Pool discovery failed at {cursor:?}: {source}")]
+pub struct DiscoveryError {
+    pub cursor: DiscoveryCursor,
+    pub source: AMMError,
+}
+
+type DiscoveryResult = Result<DiscoveredPool, DiscoveryError>;
+
+/// Streams the pools of `factory` created from `cursor` up to `to_block`, populated at
+/// `to_block`, as they are found.
+///
+/// Blocks are searched `step` at a time with at most [`MAX_CONCURRENT_EVENT_REQUESTS`] ranges
+/// in flight. A pool failing to decode or populate yields an error and discovery goes on, a
+/// range of blocks failing to load yields an error and ends the stream.
+pub fn discover_pools<P>(
+    factory: Factory,
+    cursor: DiscoveryCursor,
+    to_block: u64,
+    step: u64,
+    provider: Arc<P>,
+) -> impl Stream<Item = DiscoveryResult>
+where
+    P: Provider + Send + Sync,
+{
+    let step = step.max(1);
+
+    // mySwap pools are enumerated by id in one go
+    let ranges: Vec<(u64, u64)> = if let Factory::MySwapFactory(_) = factory {
+        vec![(cursor.block, to_block)]
+    } else {
+        (cursor.block..=to_block)
+            .step_by(step as usize)
+            .map(|range_from| {
+                (
+                    range_from,
+                    range_from.saturating_add(step - 1).min(to_block),
+                )
+            })
+            .collect()
+    };
+
+    let factory = Arc::new(factory);
+    stream::iter(ranges)
+        .map(move |(range_from, range_to)| {
+            let factory = factory.clone();
+            let provider = provider.clone();
+            async move {
+                match factory.as_ref() {
+                    Factory::MySwapFactory(myswap) => {
+                        discover_myswap_pools(myswap, cursor, to_block, provider).await
+                    }
+                    _ => {
+                        discover_pools_from_logs(
+                            &factory, cursor, range_from, range_to, step, to_block, provider,
+                        )
+                        .await
+                    }
+                }
+            }
+        })
+        .buffered(MAX_CONCURRENT_EVENT_REQUESTS)
+        .scan(false, |failed, range| {
+            if *failed {
+                return future::ready(None);
+            }
+            let items = range.unwrap_or_else(|err| {
+                *failed = true;
+                vec![Err(err)]
+            });
+            future::ready(Some(stream::iter(items)))
+        })
+        .flatten()
+}
+
+/// Streams the pools of every factory from its cursor, see [`discover_pools`]. Pools of
+/// different factories are interleaved as they are found.
+pub fn discover_all_pools<P>(
+    cursors: Vec<(Factory, DiscoveryCursor)>,
+    to_block: u64,
+    step: u64,
+    provider: Arc<P>,
+) -> impl Stream<Item = DiscoveryResult>
+where
+    P: Provider + Send + Sync,
+{
+    stream::select_all(cursors.into_iter().map(|(factory, cursor)| {
+        Box::pin(discover_pools(
+            factory,
+            cursor,
+            to_block,
+            step,
+            provider.clone(),
+        ))
+    }))
+}
+
+async fn discover_pools_from_logs<P>(
+    factory: &Factory,
+    start: DiscoveryCursor,
+    range_from: u64,
+    range_to: u64,
+    step: u64,
+    to_block: u64,
+    provider: Arc<P>,
+) -> Result<Vec<DiscoveryResult>, DiscoveryError>
+where
+    P: Provider + Send + Sync,
+{
+    let range_cursor = if range_from == start.block {
+        start
+    } else {
+        DiscoveryCursor::new(start.factory, range_from)
+    };
+
+    let logs = get_events_in_range(
+        factory.address(),
+        factory.amm_created_event_signature(),
+        range_from,
+        range_to,
+        step,
+        provider.clone(),
+    )
+    .await
+    .map_err(|source| DiscoveryError {
+        cursor: range_cursor,
+        source,
+    })?;
+
+    let decoded = cursors_after_logs(start, range_from, logs)
+        .into_iter()
+        .map(|(cursor, log)| {
+            (
+                cursor,
+                factory.new_empty_amm_from_log(log).map(|pool| pool.amm),
+            )
+        })
+        .collect();
+
+    Ok(populate_each(factory, decoded, BlockId::Number(to_block), provider).await)
+}
+
+/// Pairs every log of a range starting at `range_from` with the cursor resuming after it,
+/// dropping the logs yielded before `start`.
+fn cursors_after_logs(
+    start: DiscoveryCursor,
+    range_from: u64,
+    logs: Vec<EmittedEvent>,
+) -> Vec<(DiscoveryCursor, EmittedEvent)> {
+    let mut cursors = vec![];
+    let (mut block, mut pair_index) = (range_from, 0);
+    for log in logs {
+        let log_block = log.block_number.unwrap_or(range_from);
+        if log_block != block {
+            (block, pair_index) = (log_block, 0);
+        }
+        pair_index += 1;
+
+        // Skip the pools yielded before the cursor
+        if block == start.block && pair_index <= start.pair_index {
+            continue;
+        }
+
+        let cursor = DiscoveryCursor {
+            factory: start.factory,
+            block,
+            pair_index,
+        };
+        cursors.push((cursor, log));
+    }
+
+    cursors
+}
+
+/// Populates the decoded pools, recording the ones that fail on their own. A pool that fails
+/// gets the cursor of the item before it, so that resuming from the error retries it.
+async fn populate_each<P>(
+    factory: &Factory,
+    mut decoded: Vec<(DiscoveryCursor, Result<AMM, AMMError>)>,
+    block_id: BlockId,
+    provider: Arc<P>,
+) -> Vec<DiscoveryResult>
+where
+    P: Provider + Send + Sync,
+{
+    let (indices, mut amms): (Vec<usize>, Vec<AMM>) = decoded
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, (_, amm))| Some((idx, amm.as_ref().ok()?.clone())))
+        .unzip();
+
+    let report = populate_amms_with_report(Some(factory), &mut amms, block_id, provider).await;
+
+    for ((idx, amm), (_, result)) in indices.into_iter().zip(amms).zip(report.results) {
+        decoded[idx].1 = result.map(|_| amm);
+    }
+
+    decoded
+        .into_iter()
+        .map(|(cursor, amm)| {
+            amm.map(|amm| DiscoveredPool { amm, cursor })
+                .map_err(|source| DiscoveryError {
+                    cursor: cursor.before(),
+                    source,
+                })
+        })
+        .collect()
+}
+
+async fn discover_myswap_pools<P>(
+    factory: &MySwapFactory,
+    start: DiscoveryCursor,
+    to_block: u64,
+    provider: Arc<P>,
+) -> Result<Vec<DiscoveryResult>, DiscoveryError>
+where
+    P: Provider + Send + Sync,
+{
+    let block_id = BlockId::Number(to_block);
+    let pool_count = get_pool_count(factory.contract_address, block_id, provider.clone())
+        .await
+        .map_err(|source| DiscoveryError {
+            cursor: start,
+            source,
+        })?;
+
+    let mut pools = vec![];
+    for pool_id in myswap_pool_ids(start, pool_count) {
+        let cursor = DiscoveryCursor {
+            pair_index: pool_id,
+            ..start
+        };
+        let pool = get_pool_info(
+            factory.contract_address,
+            pool_id,
+            block_id,
+            provider.clone(),
+        )
+        .await
+        .map(|pool| DiscoveredPool {
+            amm: AMM::MySwapPool(pool),
+            cursor,
+        })
+        .map_err(|source| DiscoveryError {
+            cursor: cursor.before(),
+            source,
+        });
+        pools.push(pool);
+    }
+
+    Ok(pools)
+}
+
+/// Ids of the mySwap pools left to discover after `start`, pool ids starting at 1.
+fn myswap_pool_ids(start: DiscoveryCursor, pool_count: u64) -> RangeInclusive<u64> {
+    start.pair_index + 1..=pool_count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FACTORY: Felt = Felt::from_hex_unchecked("0xfac");
+
+    fn log_at(block: u64, tag: u64) -> EmittedEvent {
+        EmittedEvent {
+            from_address: FACTORY,
+            keys: vec![],
+            data: vec![Felt::from(tag)],
+            block_hash: None,
+            block_number: Some(block),
+            transaction_hash: Felt::ZERO,
+        }
+    }
+
+    fn cursor(block: u64, pair_index: u64) -> DiscoveryCursor {
+        DiscoveryCursor {
+            factory: FACTORY,
+            block,
+            pair_index,
+        }
+    }
+
+    #[test]
+    fn log_cursors_resume_after_their_pool() {
+        let logs = vec![log_at(10, 0), log_at(10, 1), log_at(11, 2)];
+        let cursors: Vec<DiscoveryCursor> = cursors_after_logs(cursor(10, 0), 10, logs)
+            .into_iter()
+            .map(|(cursor, _)| cursor)
+            .collect();
+
+        assert_eq!(cursors, vec![cursor(10, 1), cursor(10, 2), cursor(11, 1)]);
+    }
+
+    #[test]
+    fn resuming_from_an_error_retries_the_failed_log() {
+        let logs = || vec![log_at(10, 0), log_at(10, 1), log_at(11, 2)];
+
+        // Every log, including the first of a block, fails back to a cursor yielding it first
+        for (failed, (success, log)) in cursors_after_logs(cursor(10, 0), 10, logs())
+            .into_iter()
+            .enumerate()
+        {
+            // A resumed discovery searches the logs from the block of its cursor
+            let error_cursor = success.before();
+            let resumed_logs = logs()
+                .into_iter()
+                .filter(|log| log.block_number >= Some(error_cursor.block))
+                .collect();
+            let resumed = cursors_after_logs(error_cursor, error_cursor.block, resumed_logs);
+
+            assert_eq!(resumed.len(), 3 - failed);
+            assert_eq!(resumed[0].0, success);
+            assert_eq!(resumed[0].1.data, log.data);
+        }
+    }
+
+    #[test]
+    fn resuming_from_an_error_retries_the_failed_myswap_pool() {
+        let start = cursor(0, 3);
+        let failed_id = *myswap_pool_ids(start, 6).start();
+        let error_cursor = cursor(0, failed_id).before();
+
+        assert_eq!(myswap_pool_ids(error_cursor, 6), failed_id..=6);
+        assert_eq!(myswap_pool_ids(cursor(0, 0).before(), 6), 1..=6);
+    }
+}
@@ -0,0 +1,245 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use starknet::core::types::Felt;
+
+use crate::amm::{
+    pool::{AutomatedMarketMaker, AMM},
+    types::Amount,
+};
+
+/// Filters dropping dead or unwanted pools during discovery and checkpoint sync.
+///
+/// The default filters keep every pool.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PoolFilters {
+    /// Drop pools with a zero reserve of any of their tokens.
+    pub remove_empty: bool,
+    /// Drop pools holding less than the minimum reserve of a listed token.
+    pub min_reserves: HashMap<Felt, Amount>,
+    /// When set, drop pools with a token outside the list.
+    pub token_allow_list: Option<HashSet<Felt>>,
+}
+
+/// Number of pools removed by each filter. A pool is counted by the first filter it fails,
+/// in field order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FilterReport {
+    pub disallowed_token: usize,
+    pub empty: usize,
+    pub below_min_reserve: usize,
+}
+
+impl FilterReport {
+    /// Total number of pools removed.
+    pub fn total(&self) -> usize {
+        self.disallowed_token + self.empty + self.below_min_reserve
+    }
+
+    pub fn merge(&mut self, other: FilterReport) {
+        self.disallowed_token += other.disallowed_token;
+        self.empty += other.empty;
+        self.below_min_reserve += other.below_min_reserve;
+    }
+}
+
+impl PoolFilters {
+    pub fn new() -> PoolFilters {
+        PoolFilters::default()
+    }
+
+    pub fn remove_empty(mut self) -> PoolFilters {
+        self.remove_empty = true;
+        self
+    }
+
+    pub fn min_reserve(mut self, token: Felt, amount: Amount) -> PoolFilters {
+        self.min_reserves.insert(token, amount);
+        self
+    }
+
+    pub fn allow_tokens(mut self, tokens: impl IntoIterator<Item = Felt>) -> PoolFilters {
+        self.token_allow_list
+            .get_or_insert_with(HashSet::new)
+            .extend(tokens);
+        self
+    }
+
+    /// True when no filter is set and every pool is kept.
+    pub fn is_empty(&self) -> bool {
+        !self.remove_empty && self.min_reserves.is_empty() && self.token_allow_list.is_none()
+    }
+
+    /// Removes the pools with a token outside the allow list from `amms`, before they are
+    /// populated. Pools whose tokens are not known yet, like mySwap pools discovered by id, are
+    /// kept for [`apply`](Self::apply).
+    pub fn apply_token_allow_list(&self, amms: &mut Vec<AMM>) -> FilterReport {
+        let mut report = FilterReport::default();
+        let Some(allowed) = &self.token_allow_list else {
+            return report;
+        };
+
+        amms.retain(|amm| {
+            let tokens = amm.tokens();
+            if tokens.contains(&Felt::ZERO) || tokens.iter().all(|token| allowed.contains(token)) {
+                return true;
+            }
+
+            report.disallowed_token += 1;
+            false
+        });
+
+        report
+    }
+
+    /// Removes the pools failing a filter from `amms`. Reserves must have been synced.
+    pub fn apply(&self, amms: &mut Vec<AMM>) -> FilterReport {
+        let mut report = FilterReport::default();
+        if self.is_empty() {
+            return report;
+        }
+
+        amms.retain(|amm| {
+            let tokens = amm.tokens();
+
+            if let Some(allowed) = &self.token_allow_list {
+                if !tokens.iter().all(|token| allowed.contains(token)) {
+                    report.disallowed_token += 1;
+                    return false;
+                }
+            }
+
+            let reserves = amm.reserves();
+
+            if self.remove_empty && reserves.iter().any(Amount::is_zero) {
+                report.empty += 1;
+                return false;
+            }
+
+            let below_min_reserve = tokens.iter().zip(&reserves).any(|(token, reserve)| {
+                self.min_reserves
+                    .get(token)
+                    .is_some_and(|min_reserve| reserve < min_reserve)
+            });
+            if below_min_reserve {
+                report.below_min_reserve += 1;
+                return false;
+            }
+
+            true
+        });
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amm::{
+        myswap::pool::MySwapPool,
+        uniswap_v2::{descriptor::JEDISWAP, pool::UniswapV2Pool},
+    };
+
+    const TOKEN_A: Felt = Felt::from_hex_unchecked("0xa");
+    const TOKEN_B: Felt = Felt::from_hex_unchecked("0xb");
+    const TOKEN_C: Felt = Felt::from_hex_unchecked("0xc");
+
+    fn v2_pool(address: u64, tokens: (Felt, Felt), reserves: (u128, u128)) -> AMM {
+        AMM::UniswapV2Pool(UniswapV2Pool::new(
+            Felt::from(address),
+            tokens.0,
+            tokens.1,
+            18,
+            18,
+            Amount::from_words(reserves.0, 0),
+            Amount::from_words(reserves.1, 0),
+            JEDISWAP,
+        ))
+    }
+
+    fn addresses(amms: &[AMM]) -> Vec<Felt> {
+        amms.iter().map(AutomatedMarketMaker::address).collect()
+    }
+
+    #[test]
+    fn pools_are_counted_by_the_first_failing_filter() {
+        let filters = PoolFilters::new()
+            .allow_tokens([TOKEN_A, TOKEN_B])
+            .remove_empty()
+            .min_reserve(TOKEN_A, Amount::from_words(100, 0));
+        let mut amms = vec![
+            v2_pool(1, (TOKEN_A, TOKEN_B), (1000, 2000)),
+            // Fails every filter, counted as a disallowed token.
+            v2_pool(2, (TOKEN_A, TOKEN_C), (0, 2000)),
+            // Empty and below the minimum reserve, counted as empty.
+            v2_pool(3, (TOKEN_A, TOKEN_B), (0, 2000)),
+            v2_pool(4, (TOKEN_A, TOKEN_B), (10, 2000)),
+        ];
+
+        let report = filters.apply(&mut amms);
+
+        assert_eq!(addresses(&amms), vec![Felt::from(1u64)]);
+        assert_eq!(
+            report,
+            FilterReport {
+                disallowed_token: 1,
+                empty: 1,
+                below_min_reserve: 1,
+            }
+        );
+        assert_eq!(report.total(), 3);
+    }
+
+    #[test]
+    fn the_allow_list_keeps_pools_with_unknown_tokens_until_they_are_populated() {
+        let filters = PoolFilters::new().allow_tokens([TOKEN_A, TOKEN_B]);
+        let unpopulated = AMM::MySwapPool(MySwapPool {
+            contract_address: Felt::from(5u64),
+            pool_id: 1,
+            ..Default::default()
+        });
+        let mut amms = vec![
+            unpopulated.clone(),
+            v2_pool(1, (TOKEN_A, TOKEN_B), (1000, 2000)),
+            v2_pool(2, (TOKEN_A, TOKEN_C), (1000, 2000)),
+        ];
+
+        let report = filters.apply_token_allow_list(&mut amms);
+
+        assert_eq!(addresses(&amms), vec![Felt::from(5u64), Felt::from(1u64)]);
+        assert_eq!(
+            report,
+            FilterReport {
+                disallowed_token: 1,
+                ..Default::default()
+            }
+        );
+
+        // Once populated, its tokens are checked like any other pool.
+        let mut amms = vec![unpopulated];
+        assert_eq!(filters.apply(&mut amms).disallowed_token, 1);
+        assert!(amms.is_empty());
+    }
+
+    #[test]
+    fn min_reserves_ignore_tokens_outside_the_pool() {
+        let filters = PoolFilters::new().min_reserve(TOKEN_C, Amount::from_words(100, 0));
+        let mut amms = vec![
+            v2_pool(1, (TOKEN_A, TOKEN_B), (1, 1)),
+            v2_pool(2, (TOKEN_A, TOKEN_C), (1000, 10)),
+            v2_pool(3, (TOKEN_C, TOKEN_B), (100, 1)),
+        ];
+
+        let report = filters.apply(&mut amms);
+
+        assert_eq!(addresses(&amms), vec![Felt::from(1u64), Felt::from(3u64)]);
+        assert_eq!(
+            report,
+            FilterReport {
+                below_min_reserve: 1,
+                ..Default::default()
+            }
+        );
+    }
+}
@@ -1,5 +1,9 @@
+pub mod discovery;
+pub mod filters;
+
 use super::amm::pool::AMM;
 use std::{
+    collections::HashSet,
     fs::read_to_string,
     panic::resume_unwind,
     path::Path,
@@ -10,26 +14,38 @@ use std::{
 use serde::{Deserialize, Serialize};
 
 use starknet::{
-    core::types::{Felt, StarknetError},
+    core::types::{BlockId, Felt, StarknetError},
     providers::Provider,
 };
 use tokio::task::JoinHandle;
 
 use crate::{
     amm::{
-        factory::{AutomatedMarketMakerFactory, Factory},
-        jediswap::{self, factory::JediswapFactory},
-        tenkswap::{self, factory::TenKFactory},
+        batch::{populate_amms_with_report, PopulateReport},
+        ekubo::factory::EkuboFactory,
+        factory::Factory,
+        jediswap_v2::factory::JediswapV2Factory,
+        myswap::factory::MySwapFactory,
+        nostra::factory::NostraFactory,
+        pool::AutomatedMarketMaker,
+        sithswap::factory::SithSwapFactory,
+        types::PoolId,
+        uniswap_v2::factory::UniswapV2Factory,
     },
     errors::{AMMError, CheckpointError},
 };
 
+use self::filters::{FilterReport, PoolFilters};
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Checkpoint {
     pub timestamp: usize,
     pub block_number: u64,
     pub factories: Vec<Factory>,
     pub amms: Vec<AMM>,
+    /// New pools that could not be populated yet, retried on the next sync.
+    #[serde(default)]
+    pub unpopulated_amms: Vec<AMM>,
 }
 
 impl Checkpoint {
@@ -38,51 +54,68 @@ impl Checkpoint {
         block_number: u64,
         factories: Vec<Factory>,
         amms: Vec<AMM>,
+        unpopulated_amms: Vec<AMM>,
     ) -> Checkpoint {
         Checkpoint {
             timestamp,
             block_number,
             factories,
             amms,
+            unpopulated_amms,
         }
     }
 }
 
 // Get all pairs from last synced block and sync reserve values for each Dex in the `dexes` vec.
+// Pools failing `filters` are dropped from the result and the checkpoint. Pools that could not
+// be populated are listed in the `PopulateReport`: pools of the checkpoint are kept with their
+// previous state, new pools are held back in `PopulateReport::unpopulated` and retried on the
+// next sync.
 pub async fn sync_amms_from_checkpoint<P, A>(
     path_to_checkpoint: A,
     step: u64,
+    filters: &PoolFilters,
     provider: Arc<P>,
-) -> Result<(Vec<Factory>, Vec<AMM>), AMMError>
+) -> Result<(Vec<Factory>, Vec<AMM>, FilterReport, PopulateReport), AMMError>
 where
     P: Provider + Send + Sync + 'static,
     A: AsRef<Path>,
 {
-    let current_block = provider.block_number().await.unwrap();
+    let current_block = provider.block_number().await?;
 
     let checkpoint: Checkpoint =
         serde_json::from_str(read_to_string(&path_to_checkpoint)?.as_str())?;
 
-    // Sort all of the pools from the checkpoint into uniswap_v2_pools and uniswap_v3_pools pools so we can sync them concurrently
-    let (jediswap_pools, tenkswap_pools) = sort_amms(checkpoint.amms);
+    // Sort all of the pools from the checkpoint by pool type so we can sync them concurrently
+    let sorted_amms = sort_amms(checkpoint.amms);
 
     let mut aggregated_amms = vec![];
+    let mut report = FilterReport::default();
+    let mut populate_report = PopulateReport::default();
     let mut handles = vec![];
 
-    // Sync all uniswap v2 pools from checkpoint
-    if !jediswap_pools.is_empty() {
+    // Sync all pools of each type from checkpoint
+    for amms in sorted_amms {
         handles.push(
-            batch_sync_amms_from_checkpoint(jediswap_pools, Some(current_block), provider.clone())
-                .await,
+            batch_sync_amms_from_checkpoint(
+                amms,
+                BlockId::Number(current_block),
+                filters,
+                provider.clone(),
+            )
+            .await,
         );
     }
 
-    // Sync all uniswap v2 pools from checkpoint
-    if !tenkswap_pools.is_empty() {
-        handles.push(
-            batch_sync_amms_from_checkpoint(tenkswap_pools, Some(current_block), provider.clone())
-                .await,
-        );
+    // Retry the new pools held back by the last sync
+    for amms in sort_amms(checkpoint.unpopulated_amms) {
+        handles.push(spawn_batch_sync(
+            amms,
+            BlockId::Number(current_block),
+            filters,
+            true,
+            provider.clone(),
+        ));
     }
 
     // Sync all pools from the since synced block
@@ -92,6 +125,7 @@ where
             checkpoint.block_number,
             current_block,
             step,
+            filters,
             provider.clone(),
         )
         .await,
@@ -99,7 +133,12 @@ where
 
     for handle in handles {
         match handle.await {
-            Ok(sync_result) => aggregated_amms.extend(sync_result?),
+            Ok(sync_result) => {
+                let (amms, removed, populated) = sync_result?;
+                aggregated_amms.extend(amms);
+                report.merge(removed);
+                populate_report.merge(populated);
+            }
             Err(err) => {
                 {
                     if err.is_panic() {
@@ -111,16 +150,37 @@ where
         }
     }
 
+    // Pools found again from the factories may already be in the checkpoint
+    let mut seen = HashSet::new();
+    aggregated_amms.retain(|amm| seen.insert(amm.id()));
+    populate_report
+        .unpopulated
+        .retain(|amm| seen.insert(amm.id()));
+
     //update the sync checkpoint
     save_checkpoint(
         checkpoint.factories.clone(),
         &aggregated_amms,
+        &populate_report.unpopulated,
         current_block,
         path_to_checkpoint,
     )
     .unwrap();
 
-    Ok((checkpoint.factories, aggregated_amms))
+    tracing::info!(?report, "Pools removed by filters");
+    if !populate_report.is_ok() {
+        tracing::warn!(
+            failed = populate_report.failed().count(),
+            "Pools could not be populated"
+        );
+    }
+
+    Ok((
+        checkpoint.factories,
+        aggregated_amms,
+        report,
+        populate_report,
+    ))
 }
 
 pub async fn get_new_amms_from_range<P>(
@@ -128,8 +188,9 @@ pub async fn get_new_amms_from_range<P>(
     from_block: u64,
     to_block: u64,
     step: u64,
+    filters: &PoolFilters,
     provider: Arc<P>,
-) -> Vec<JoinHandle<Result<Vec<AMM>, AMMError>>>
+) -> Vec<JoinHandle<Result<(Vec<AMM>, FilterReport, PopulateReport), AMMError>>>
 where
     P: Provider + Send + Sync + 'static,
 {
@@ -139,20 +200,25 @@ where
 
     for factory in factories.into_iter() {
         let provider = provider.clone();
+        let filters = filters.clone();
 
         // Spawn a new thread to get all pools and sync data for each dex
         handles.push(tokio::spawn(async move {
-            let mut amms = factory
+            let amms = factory
                 .get_all_pools_from_logs(from_block, to_block, step, provider.clone())
                 .await?;
 
-            factory
-                .populate_amm_data(&mut amms, Some(to_block), provider.clone())
-                .await?;
-
-            // TODO :  Clean empty pools
-
-            Ok::<_, AMMError>(amms)
+            Ok::<_, AMMError>(
+                populate_filtered(
+                    Some(&factory),
+                    amms,
+                    BlockId::Number(to_block),
+                    &filters,
+                    true,
+                    provider,
+                )
+                .await,
+            )
         }));
     }
 
@@ -160,50 +226,130 @@ where
 }
 
 pub async fn batch_sync_amms_from_checkpoint<P>(
-    mut amms: Vec<AMM>,
-    block_number: Option<u64>,
+    amms: Vec<AMM>,
+    block_id: BlockId,
+    filters: &PoolFilters,
+    provider: Arc<P>,
+) -> JoinHandle<Result<(Vec<AMM>, FilterReport, PopulateReport), AMMError>>
+where
+    P: Provider + Send + Sync + 'static,
+{
+    spawn_batch_sync(amms, block_id, filters, false, provider)
+}
+
+/// Populates `amms`, all of one type, on a new task. Pools that fail are held back in the
+/// `PopulateReport` when `new_pools` is set, since they have no previous state to keep.
+fn spawn_batch_sync<P>(
+    amms: Vec<AMM>,
+    block_id: BlockId,
+    filters: &PoolFilters,
+    new_pools: bool,
     provider: Arc<P>,
-) -> JoinHandle<Result<Vec<AMM>, AMMError>>
+) -> JoinHandle<Result<(Vec<AMM>, FilterReport, PopulateReport), AMMError>>
 where
     P: Provider + Send + Sync + 'static,
 {
     let factory = match amms[0] {
-        AMM::JediswapPool(_) => Some(Factory::JediswapFactory(JediswapFactory::new(Felt::ZERO))),
-        AMM::TenkSwapPool(_) => Some(Factory::TenKFactory(TenKFactory::new(Felt::ZERO))),
+        AMM::EkuboPool(_) => Some(Factory::EkuboFactory(EkuboFactory::new(Felt::ZERO, 0))),
+        AMM::JediswapV2Pool(_) => Some(Factory::JediswapV2Factory(JediswapV2Factory::new(
+            Felt::ZERO,
+            0,
+        ))),
+        AMM::MySwapPool(_) => Some(Factory::MySwapFactory(MySwapFactory::new(Felt::ZERO))),
+        AMM::SithSwapPool(_) => Some(Factory::SithSwapFactory(SithSwapFactory::new(Felt::ZERO))),
+        AMM::NostraPool(_) => Some(Factory::NostraFactory(NostraFactory::new(Felt::ZERO))),
+        AMM::ERC4626Vault(_) => None,
+        AMM::UniswapV2Pool(ref pool) => Some(Factory::UniswapV2Factory(UniswapV2Factory::new(
+            Felt::ZERO,
+            pool.descriptor.clone(),
+        ))),
     };
 
+    let filters = filters.clone();
+
     // Spawn a new thread to get all pools and sync data for each dex
     tokio::spawn(async move {
-        if let Some(factory) = factory {
-            if amms_are_congruent(&amms) {
-                // Get all pool data via batched calls
-                factory
-                    .populate_amm_data(&mut amms, block_number, provider)
-                    .await?;
-
-                // TODO : Clean empty pools
-
-                Ok::<_, AMMError>(amms)
-            } else {
-                Err(AMMError::IncongruentAMMs)
-            }
-        } else {
-            Ok::<_, AMMError>(vec![])
+        if factory.is_some() && !amms_are_congruent(&amms) {
+            return Err(AMMError::IncongruentAMMs);
         }
+
+        // Get all pool data via batched calls, pools without a factory only need their
+        // state read
+        Ok::<_, AMMError>(
+            populate_filtered(
+                factory.as_ref(),
+                amms,
+                block_id,
+                &filters,
+                new_pools,
+                provider,
+            )
+            .await,
+        )
     })
 }
 
-pub fn sort_amms(amms: Vec<AMM>) -> (Vec<AMM>, Vec<AMM>) {
-    let mut jediswap_pools = vec![];
-    let mut tenkswap_pools = vec![];
+/// Populates `amms` with batched calls and filters them. Pools with a token outside the allow
+/// list are dropped before being populated, since their tokens are known from the creation log
+/// or the checkpoint.
+async fn populate_filtered<P>(
+    factory: Option<&Factory>,
+    mut amms: Vec<AMM>,
+    block_id: BlockId,
+    filters: &PoolFilters,
+    new_pools: bool,
+    provider: Arc<P>,
+) -> (Vec<AMM>, FilterReport, PopulateReport)
+where
+    P: Provider + Send + Sync,
+{
+    let mut report = filters.apply_token_allow_list(&mut amms);
+
+    let mut populate_report =
+        populate_amms_with_report(factory, &mut amms, block_id, provider).await;
+    let (amms, populated_report) = filter_populated(filters, amms, &mut populate_report, new_pools);
+    report.merge(populated_report);
+
+    (amms, report, populate_report)
+}
+
+/// Applies `filters` to the pools that were populated. Pools that failed to populate are kept
+/// as they are, so that the next sync from the checkpoint retries them, unless they are
+/// `new_pools`: having no state yet, those are moved to `populate_report.unpopulated`.
+fn filter_populated(
+    filters: &PoolFilters,
+    amms: Vec<AMM>,
+    populate_report: &mut PopulateReport,
+    new_pools: bool,
+) -> (Vec<AMM>, FilterReport) {
+    let failed: HashSet<PoolId> = populate_report.failed().cloned().collect();
+    let (mut populated, unpopulated): (Vec<AMM>, Vec<AMM>) = amms
+        .into_iter()
+        .partition(|amm| !failed.contains(&amm.id()));
+
+    let report = filters.apply(&mut populated);
+    if new_pools {
+        populate_report.unpopulated.extend(unpopulated);
+    } else {
+        populated.extend(unpopulated);
+    }
+
+    (populated, report)
+}
+
+pub fn sort_amms(amms: Vec<AMM>) -> Vec<Vec<AMM>> {
+    let mut sorted_amms: Vec<Vec<AMM>> = vec![];
     for amm in amms {
-        match amm {
-            AMM::JediswapPool(_) => jediswap_pools.push(amm),
-            AMM::TenkSwapPool(_) => tenkswap_pools.push(amm),
+        match sorted_amms
+            .iter_mut()
+            .find(|group| std::mem::discriminant(&group[0]) == std::mem::discriminant(&amm))
+        {
+            Some(group) => group.push(amm),
+            None => sorted_amms.push(vec![amm]),
         }
     }
 
-    (jediswap_pools, tenkswap_pools)
+    sorted_amms
 }
 
 pub fn amms_are_congruent(amms: &[AMM]) -> bool {
@@ -222,8 +368,9 @@ pub async fn get_new_pools_from_range<P>(
     from_block: u64,
     to_block: u64,
     step: u64,
+    filters: &PoolFilters,
     provider: Arc<P>,
-) -> Vec<JoinHandle<Result<Vec<AMM>, AMMError>>>
+) -> Vec<JoinHandle<Result<(Vec<AMM>, FilterReport, PopulateReport), AMMError>>>
 where
     P: Provider + Send + Sync + 'static,
 {
@@ -233,20 +380,25 @@ where
 
     for factory in factories {
         let provider = provider.clone();
+        let filters = filters.clone();
 
         // Spawn a new thread to get all pools and sync data for each dex
         handles.push(tokio::spawn(async move {
-            let mut pools = factory
+            let pools = factory
                 .get_all_pools_from_logs(from_block, to_block, step, provider.clone())
                 .await?;
 
-            factory
-                .populate_amm_data(&mut pools, Some(to_block), provider.clone())
-                .await?;
-
-            // TODO :Clean empty pools
-
-            Ok::<_, AMMError>(pools)
+            Ok::<_, AMMError>(
+                populate_filtered(
+                    Some(&factory),
+                    pools,
+                    BlockId::Number(to_block),
+                    &filters,
+                    true,
+                    provider,
+                )
+                .await,
+            )
         }));
     }
 
@@ -256,6 +408,7 @@ where
 pub fn save_checkpoint<P>(
     factories: Vec<Factory>,
     amms: &[AMM],
+    unpopulated_amms: &[AMM],
     latest_block: u64,
     checkpoint_path: P,
 ) -> Result<(), CheckpointError>
@@ -267,6 +420,7 @@ where
         latest_block,
         factories,
         amms.to_vec(),
+        unpopulated_amms.to_vec(),
     );
 
     std::fs::write(checkpoint_path, serde_json::to_string_pretty(&checkpoint)?).unwrap();
@@ -1,5 +1,5 @@
 use starknet::{
-    core::types::{Felt, U256},
+    core::types::{Felt, StarknetError, U256},
     providers::ProviderError,
 };
 use std::time::SystemTimeError;
@@ -24,6 +24,8 @@ pub enum AMMError {
     SyncError(Felt),
     #[error("Error when getting pool data")]
     PoolDataError,
+    #[error("Batched request failed")]
+    BatchRequestError,
     #[error(transparent)]
     ArithmeticError(#[from] ArithmeticError),
     #[error("No initialized ticks during v3 swap simulation")]
@@ -34,6 +36,14 @@ pub enum AMMError {
     IncongruentAMMs,
     #[error("Invalid ERC4626 fee")]
     InvalidERC4626Fee,
+    #[error("Pool {0:#x} has no router to cross-check its quotes against")]
+    NoQuoteRouter(Felt),
+    #[error("Router quote failed for pool {0:#x}")]
+    RouterQuoteError(Felt),
+    #[error("Price impact must be between 0 and 1, got {0}")]
+    InvalidPriceImpact(f64),
+    #[error("Spot price of pool {0:#x} is zero, price impact is undefined")]
+    ZeroSpotPrice(Felt),
     #[error(transparent)]
     EventLogError(#[from] EventLogError),
     #[error("Block number not found")]
@@ -62,6 +72,10 @@ pub enum ArithmeticError {
     BaseTokenDoesNotExist,
     #[error("quote token does not exist in pool")]
     QuoteTokenDoesNotExist,
+    #[error("Price does not fit in a Decimal")]
+    DecimalOverflow,
+    #[error("Amount underflow")]
+    AmountUnderflow,
 }
 
 #[derive(Error, Debug)]
@@ -74,10 +88,24 @@ pub enum EventLogError {
 
 #[derive(Error, Debug)]
 pub enum SwapSimulationError {
+    #[error("token_in does not exist in pool")]
+    TokenInDoesNotExist(Felt),
+    #[error("token_out does not exist in pool")]
+    TokenOutDoesNotExist(Felt),
+    #[error("token_in and token_out are the same token")]
+    IdenticalTokens,
+    #[error("No initialized ticks during v3 swap simulation")]
+    NoInitializedTicks,
+    #[error("Invalid pool fee")]
+    InvalidFee,
     #[error("Could not get next tick")]
     InvalidTick,
     #[error("Liquidity underflow")]
     LiquidityUnderflow,
+    #[error("Not enough liquidity for the requested output")]
+    InsufficientLiquidity,
+    #[error("Hop {0} of the route does not swap the token out of the previous hop")]
+    BrokenRoute(usize),
     #[error(transparent)]
     ArithmeticError(#[from] ArithmeticError),
 }
@@ -87,5 +115,11 @@ pub enum CheckpointError {
     #[error(transparent)]
     SystemTimeError(#[from] SystemTimeError),
     #[error(transparent)]
-    SerdeJsonError(#[from] serde_json::error::Error)
+    SerdeJsonError(#[from] serde_json::error::Error),
+}
+
+impl From<AMMError> for StarknetError {
+    fn from(err: AMMError) -> Self {
+        StarknetError::UnexpectedError(err.to_string())
+    }
 }
@@ -2,10 +2,12 @@ use color_eyre::{eyre::eyre, Result};
 use starknet::{
     accounts::SingleOwnerAccount,
     core::{
-        types::{BlockId, BlockTag, Felt, FunctionCall},
+        types::{requests::CallRequest, BlockId, Felt, FunctionCall},
         utils::get_selector_from_name,
     },
-    providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider},
+    providers::{
+        jsonrpc::HttpTransport, JsonRpcClient, Provider, ProviderRequestData, ProviderResponseData,
+    },
     signers::LocalWallet,
 };
 use std::sync::Arc;
@@ -13,24 +15,118 @@ use std::sync::Arc;
 pub type LocalWalletSignerMiddleware =
     Arc<SingleOwnerAccount<Arc<JsonRpcClient<HttpTransport>>, LocalWallet>>;
 
+/// Maximum number of calls packed into one JSON-RPC batch request.
+pub const MAX_BATCH_SIZE: usize = 100;
+
+/// Calls `method` of the contract at `address` against the state at `block_id`.
 pub async fn call_contract<P>(
     provider: Arc<P>,
     address: Felt,
     method: &str,
     calldata: Vec<Felt>,
+    block_id: BlockId,
 ) -> Result<Vec<Felt>>
 where
     P: Provider + Sync + Send,
 {
+    provider
+        .call(function_call(address, method, calldata)?, block_id)
+        .await
+        .map_err(|e| eyre!("Provider error: {}", e))
+}
+
+/// Runs every call of `calls` against the state at `block_id`, packing them into JSON-RPC
+/// batch requests of at most [`MAX_BATCH_SIZE`] calls. Results follow the order of `calls`,
+/// and the whole batch fails if any call does.
+pub async fn batch_call_contracts<P>(
+    provider: Arc<P>,
+    calls: Vec<FunctionCall>,
+    block_id: BlockId,
+) -> Result<Vec<Vec<Felt>>>
+where
+    P: Provider + Sync + Send,
+{
+    let mut results = Vec::with_capacity(calls.len());
+
+    for chunk in calls.chunks(MAX_BATCH_SIZE) {
+        let requests: Vec<ProviderRequestData> = chunk
+            .iter()
+            .map(|request| {
+                ProviderRequestData::Call(CallRequest {
+                    request: request.clone(),
+                    block_id,
+                })
+            })
+            .collect();
+
+        let responses = provider
+            .batch_requests(requests)
+            .await
+            .map_err(|e| eyre!("Provider error: {}", e))?;
+
+        for response in responses {
+            match response {
+                ProviderResponseData::Call(result) => results.push(result),
+                _ => return Err(eyre!("Unexpected response to a call request")),
+            }
+        }
+    }
+
+    Ok(results)
+}
+
+/// Builds the call of `method` of the contract at `address`.
+pub fn function_call(address: Felt, method: &str, calldata: Vec<Felt>) -> Result<FunctionCall> {
     let entry_point_selector = get_selector_from_name(method)
         .map_err(|e| eyre!("Invalid selector for {}: {}", method, e))?;
-    let function_call = FunctionCall {
+
+    Ok(FunctionCall {
         contract_address: address,
         entry_point_selector,
         calldata,
-    };
-    provider
-        .call(function_call, BlockId::Tag(BlockTag::Latest))
-        .await
-        .map_err(|e| eyre!("Provider error: {}", e))
+    })
+}
+
+/// Decodes a Cairo array, returned as its length followed by its elements. `None` when
+/// fewer elements than announced were returned.
+pub fn decode_felt_array(result: &[Felt]) -> Option<&[Felt]> {
+    let (length, elements) = result.split_first()?;
+    elements.get(..felt_to_u128(*length) as usize)
+}
+
+/// Reads the low 128 bits of a felt, as returned for Cairo `u128` values.
+pub fn felt_to_u128(value: Felt) -> u128 {
+    u128::from_le_bytes(value.to_bytes_le()[0..16].try_into().unwrap())
+}
+
+/// Reads the low byte of a felt, as returned for ERC20 `decimals`.
+pub fn felt_to_u8(value: Felt) -> u8 {
+    u8::from_le_bytes(value.to_bytes_le()[0..1].try_into().unwrap())
+}
+
+/// Decodes a Cairo `i129 { mag, sign }` into a signed integer.
+pub fn decode_i129(mag: Felt, sign: Felt) -> i128 {
+    let mag = felt_to_u128(mag) as i128;
+    if sign == Felt::ZERO {
+        mag
+    } else {
+        -mag
+    }
+}
+
+/// Encodes a signed integer as Cairo `i129 { mag, sign }` calldata.
+pub fn encode_i129(value: i128) -> Vec<Felt> {
+    vec![
+        Felt::from(value.unsigned_abs()),
+        if value < 0 { Felt::ONE } else { Felt::ZERO },
+    ]
+}
+
+/// Decodes a native Cairo signed integer, where negative values wrap around the field prime.
+pub fn felt_to_i128(value: Felt) -> i128 {
+    if value > Felt::from(i128::MAX) {
+        -(felt_to_u128(-value) as i128)
+    } else {
+        felt_to_u128(value) as i128
+    }
 }